@@ -10,7 +10,7 @@ use quick_xml::{
 };
 
 fn main() -> Result<(), io::Error> {
-    let path = match env::args().skip(1).next().map(PathBuf::from) {
+    let path = match env::args().nth(1).map(PathBuf::from) {
         Some(ref p) if p.exists() && p.extension() == Some(OsStr::new("glif")) => p.to_owned(),
         Some(ref p) => {
             eprintln!("path {:?} is not an existing .glif file, exiting", p);
@@ -34,7 +34,7 @@ fn main() -> Result<(), io::Error> {
 }
 
 fn print_tokens(xml: &str) -> Result<(), Error> {
-    let mut reader = Reader::from_str(&xml);
+    let mut reader = Reader::from_str(xml);
     let mut buf = Vec::new();
     reader.trim_text(true);
     let mut level = 0;
@@ -55,7 +55,7 @@ fn print_tokens(xml: &str) -> Result<(), Error> {
                 eprint!("{}<{}", spaces_for_level(level), name);
                 for attr in start.attributes() {
                     let attr = attr?;
-                    let key = std::str::from_utf8(&attr.key)?;
+                    let key = std::str::from_utf8(attr.key)?;
                     let value = attr.unescaped_value()?;
                     let value = reader.decode(&value);
                     eprint!(" {}=\"{}\"", key, value);
@@ -73,7 +73,7 @@ fn print_tokens(xml: &str) -> Result<(), Error> {
                 eprint!("{}<{}", spaces_for_level(level), name);
                 for attr in start.attributes() {
                     let Attribute { key, value } = attr?;
-                    let key = std::str::from_utf8(&key)?;
+                    let key = std::str::from_utf8(key)?;
                     let value = std::str::from_utf8(&value)?;
                     eprint!(" {}=\"{}\"", key, value);
                 }
@@ -0,0 +1,360 @@
+//! The [data] and [images] directories of a font.
+//!
+//! [data]: http://unifiedfontobject.org/versions/ufo3/data/
+//! [images]: http://unifiedfontobject.org/versions/ufo3/images/
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::layer::is_same_dir;
+use crate::Error;
+
+/// The first eight bytes of every PNG file.
+static PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The files in a font's `data` directory, by path relative to that directory.
+///
+/// This is where applications and tools can store arbitrary data. Files are
+/// listed when the font is loaded, but their contents are only read when
+/// they are first requested.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataStore(Store);
+
+/// The PNG images in a font's `images` directory, by file name.
+///
+/// Glyphs refer to these images through [`Image::file_name`]. Like the
+/// [`DataStore`], images are only read when they are first requested; every
+/// image is checked for the PNG signature when it is read or added.
+///
+/// When a font is saved, images that no glyph refers to are not written.
+///
+/// [`Image::file_name`]: glyph/struct.Image.html#structfield.file_name
+/// [`DataStore`]: struct.DataStore.html
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageStore(Store);
+
+/// Files loaded lazily from a directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Store {
+    /// The directory the store was loaded from.
+    source: Option<PathBuf>,
+    /// The contents of each file, or `None` if it hasn't been read yet.
+    files: BTreeMap<PathBuf, Option<Vec<u8>>>,
+}
+
+impl DataStore {
+    /// Create a new, empty store.
+    pub fn new() -> Self {
+        DataStore::default()
+    }
+
+    /// List the files in `dir` and its subdirectories, without reading them.
+    pub(crate) fn load(dir: &Path) -> Result<DataStore, Error> {
+        Store::load(dir, true).map(DataStore)
+    }
+
+    /// Returns `true` if the store contains no files.
+    pub fn is_empty(&self) -> bool {
+        self.0.files.is_empty()
+    }
+
+    /// Returns the number of files in the store.
+    pub fn len(&self) -> usize {
+        self.0.files.len()
+    }
+
+    /// Returns an iterator over the paths of all files, in sorted order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.0.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns `true` if the store contains a file at `path`.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.0.files.contains_key(path.as_ref())
+    }
+
+    /// Returns the contents of the file at `path`, reading it from disk if
+    /// necessary, or `None` if there is no such file.
+    pub fn get<P: AsRef<Path>>(&mut self, path: P) -> Option<Result<&[u8], Error>> {
+        self.0.get(path.as_ref())
+    }
+
+    /// Add a file at `path`, replacing any existing file.
+    ///
+    /// Returns [`Error::InvalidDataPath`] if `path` is not a relative path
+    /// inside the data directory.
+    ///
+    /// [`Error::InvalidDataPath`]: enum.Error.html#variant.InvalidDataPath
+    pub fn insert<P: Into<PathBuf>>(&mut self, path: P, data: Vec<u8>) -> Result<(), Error> {
+        let path = path.into();
+        let is_relative = path.components().next().is_some()
+            && path.components().all(|c| matches!(c, Component::Normal(_)));
+        if !is_relative {
+            return Err(Error::InvalidDataPath(path));
+        }
+        self.0.files.insert(path, Some(data));
+        Ok(())
+    }
+
+    /// Remove the file at `path`.
+    ///
+    /// The file will be deleted when the font is saved.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) {
+        self.0.files.remove(path.as_ref());
+    }
+
+    /// Write the store to the directory at `dir`.
+    ///
+    /// Files that haven't been read are copied from the source directory,
+    /// and files in `dir` that aren't in the store are deleted. If the store
+    /// is empty, `dir` is removed.
+    pub(crate) fn save(&self, dir: &Path) -> Result<(), Error> {
+        self.0.save(dir, |_| true)
+    }
+}
+
+impl ImageStore {
+    /// Create a new, empty store.
+    pub fn new() -> Self {
+        ImageStore::default()
+    }
+
+    /// List the files in `dir`, without reading them.
+    pub(crate) fn load(dir: &Path) -> Result<ImageStore, Error> {
+        Store::load(dir, false).map(ImageStore)
+    }
+
+    /// Returns `true` if the store contains no images.
+    pub fn is_empty(&self) -> bool {
+        self.0.files.is_empty()
+    }
+
+    /// Returns the number of images in the store.
+    pub fn len(&self) -> usize {
+        self.0.files.len()
+    }
+
+    /// Returns an iterator over the file names of all images, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &Path> {
+        self.0.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns `true` if the store contains an image with this file name.
+    pub fn contains<P: AsRef<Path>>(&self, name: P) -> bool {
+        self.0.files.contains_key(name.as_ref())
+    }
+
+    /// Returns the contents of the named image, reading it from disk if
+    /// necessary, or `None` if there is no such image.
+    ///
+    /// Returns [`Error::InvalidImage`] if the file is not a PNG image.
+    ///
+    /// [`Error::InvalidImage`]: enum.Error.html#variant.InvalidImage
+    pub fn get<P: AsRef<Path>>(&mut self, name: P) -> Option<Result<&[u8], Error>> {
+        let name = name.as_ref();
+        match self.0.get(name)? {
+            Ok(data) if !data.starts_with(PNG_SIGNATURE) => {
+                Some(Err(Error::InvalidImage(name.to_path_buf())))
+            }
+            other => Some(other),
+        }
+    }
+
+    /// Add an image with this file name, replacing any existing image.
+    ///
+    /// Returns [`Error::InvalidImage`] if `data` is not a PNG image, or if
+    /// `name` is not a plain file name.
+    ///
+    /// [`Error::InvalidImage`]: enum.Error.html#variant.InvalidImage
+    pub fn insert<P: Into<PathBuf>>(&mut self, name: P, data: Vec<u8>) -> Result<(), Error> {
+        let name = name.into();
+        let mut components = name.components();
+        let is_file_name =
+            matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none();
+        if !is_file_name || !data.starts_with(PNG_SIGNATURE) {
+            return Err(Error::InvalidImage(name));
+        }
+        self.0.files.insert(name, Some(data));
+        Ok(())
+    }
+
+    /// Remove the named image.
+    ///
+    /// The file will be deleted when the font is saved.
+    pub fn remove<P: AsRef<Path>>(&mut self, name: P) {
+        self.0.files.remove(name.as_ref());
+    }
+
+    /// Write the images for which `in_use` returns `true` to `dir`, deleting
+    /// everything else in `dir`.
+    pub(crate) fn save(&self, dir: &Path, in_use: impl Fn(&Path) -> bool) -> Result<(), Error> {
+        self.0.save(dir, in_use)
+    }
+}
+
+impl Store {
+    /// List the files in `dir`, and in its subdirectories if `recursive` is set.
+    fn load(dir: &Path, recursive: bool) -> Result<Store, Error> {
+        let mut files = BTreeMap::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(next) = dirs.pop() {
+            for entry in fs::read_dir(next)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    if recursive {
+                        dirs.push(path);
+                    }
+                } else if let Ok(relative) = path.strip_prefix(dir) {
+                    files.insert(relative.to_path_buf(), None);
+                }
+            }
+        }
+        Ok(Store { source: Some(dir.to_path_buf()), files })
+    }
+
+    fn get(&mut self, path: &Path) -> Option<Result<&[u8], Error>> {
+        let source = self.source.as_ref();
+        let data = self.files.get_mut(path)?;
+        if data.is_none() {
+            let full_path = source.expect("unread files always have a source").join(path);
+            match fs::read(full_path) {
+                Ok(bytes) => *data = Some(bytes),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+        data.as_deref().map(Ok)
+    }
+
+    /// Write the files for which `keep` returns `true` to `dir`.
+    ///
+    /// Files that haven't been read are copied from the source directory,
+    /// and all other files in `dir` are deleted. If nothing is kept, `dir`
+    /// is removed.
+    fn save(&self, dir: &Path, keep: impl Fn(&Path) -> bool) -> Result<(), Error> {
+        let is_kept = |path: &Path| self.files.contains_key(path) && keep(path);
+        if !self.files.keys().any(|path| keep(path)) {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+            return Ok(());
+        }
+
+        let source = self.source.as_ref();
+        let same_dir = source.map(|source| is_same_dir(source, dir)).unwrap_or(false);
+        if dir.exists() {
+            remove_stale_files(dir, dir, &is_kept)?;
+        }
+        for (path, data) in self.files.iter().filter(|(path, _)| keep(path)) {
+            let target = dir.join(path);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            match data {
+                Some(data) => fs::write(target, data)?,
+                None if same_dir => (),
+                None => {
+                    let source = source.expect("unread files always have a source");
+                    fs::copy(source.join(path), target)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Remove files in `dir` that aren't kept, along with any directories left
+/// empty. Paths are checked relative to `root`.
+fn remove_stale_files(
+    root: &Path,
+    dir: &Path,
+    is_kept: &impl Fn(&Path) -> bool,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_stale_files(root, &path, is_kept)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        } else if !path.strip_prefix(root).map(is_kept).unwrap_or(false) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_and_save() {
+        let mut store = DataStore::load(Path::new("testdata/datatest.ufo/data")).unwrap();
+        let paths: Vec<_> = store.paths().map(Path::to_path_buf).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("com.example.tool/config.txt"), PathBuf::from("org.example.notes")]
+        );
+        assert_eq!(store.0.files.values().filter(|d| d.is_some()).count(), 0);
+        let notes = store.get("org.example.notes").unwrap().unwrap().to_vec();
+        assert_eq!(notes, b"Remember the kerning.\n");
+        assert!(store.get("missing").is_none());
+
+        store.remove("com.example.tool/config.txt");
+        store.insert("com.example.other/data.bin", vec![0, 1, 2]).unwrap();
+
+        let dir = tempdir::TempDir::new("data").unwrap();
+        let data_dir = dir.path().join("data");
+        fs::create_dir_all(data_dir.join("com.example.tool")).unwrap();
+        fs::write(data_dir.join("com.example.tool/config.txt"), "stale").unwrap();
+        store.save(&data_dir).unwrap();
+
+        let mut loaded = DataStore::load(&data_dir).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(!data_dir.join("com.example.tool").exists());
+        assert_eq!(loaded.get("org.example.notes").unwrap().unwrap(), &notes[..]);
+        assert_eq!(loaded.get("com.example.other/data.bin").unwrap().unwrap(), &[0, 1, 2]);
+
+        DataStore::new().save(&data_dir).unwrap();
+        assert!(!data_dir.exists());
+    }
+
+    #[test]
+    fn images() {
+        let mut store = ImageStore::load(Path::new("testdata/imagetest.ufo/images")).unwrap();
+        let names: Vec<_> = store.names().map(Path::to_path_buf).collect();
+        assert_eq!(names, vec![PathBuf::from("not a png.png"), PathBuf::from("sketch.png")]);
+        assert!(store.get("sketch.png").unwrap().unwrap().starts_with(PNG_SIGNATURE));
+        match store.get("not a png.png") {
+            Some(Err(Error::InvalidImage(name))) => assert_eq!(name, Path::new("not a png.png")),
+            other => panic!("expected invalid image, found {:?}", other),
+        }
+
+        assert!(store.insert("text.png", b"hello".to_vec()).is_err());
+        assert!(store.insert("dir/new.png", PNG_SIGNATURE.to_vec()).is_err());
+        store.insert("new.png", PNG_SIGNATURE.to_vec()).unwrap();
+
+        let dir = tempdir::TempDir::new("images").unwrap();
+        let images_dir = dir.path().join("images");
+        store.save(&images_dir, |name| name != Path::new("not a png.png")).unwrap();
+        let loaded = ImageStore::load(&images_dir).unwrap();
+        let names: Vec<_> = loaded.names().map(Path::to_path_buf).collect();
+        assert_eq!(names, vec![PathBuf::from("new.png"), PathBuf::from("sketch.png")]);
+
+        store.save(&images_dir, |_| false).unwrap();
+        assert!(!images_dir.exists());
+    }
+
+    #[test]
+    fn insert_outside() {
+        let mut store = DataStore::new();
+        for path in ["../escape", "a/../../escape", "/tmp/escape", ""].iter() {
+            match store.insert(*path, Vec::new()) {
+                Err(Error::InvalidDataPath(bad)) => assert_eq!(bad, Path::new(path)),
+                other => panic!("expected invalid data path, found {:?}", other),
+            }
+        }
+        assert!(store.is_empty());
+    }
+}
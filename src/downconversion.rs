@@ -0,0 +1,227 @@
+//! Converting the UFO3 model to data that can be written as UFO2.
+
+use std::collections::BTreeMap;
+
+use crate::fontinfo::FontInfo;
+use crate::glyph::{Contour, ContourPoint, GlifVersion, Glyph, PointType};
+use crate::groups::Groups;
+use crate::kerning::Kerning;
+use crate::upconversion::KerningGroupRenames;
+
+/// Data that could not be represented when a font was saved in an older
+/// version of the UFO format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataLoss {
+    /// A layer other than the default layer, by name.
+    Layer(String),
+    /// The color, guidelines or lib of the default layer.
+    LayerInfo,
+    /// The contents of the `data` directory.
+    Data,
+    /// The contents of the `images` directory.
+    Images,
+    /// A `fontinfo.plist` key that does not exist in the older format.
+    FontInfo(&'static str),
+    /// The guidelines of the named glyph.
+    GlyphGuidelines(String),
+    /// The image of the named glyph.
+    GlyphImage(String),
+    /// The note of the named glyph.
+    GlyphNote(String),
+    /// Identifiers on the named glyph's anchors, contours, points or components.
+    GlyphIdentifiers(String),
+    /// Colors of the named glyph's anchors.
+    AnchorColors(String),
+}
+
+/// Returns a copy of `font_info` without the keys that were added in UFO3.
+pub(crate) fn font_info_to_v2(font_info: &FontInfo, losses: &mut Vec<DataLoss>) -> FontInfo {
+    let mut info = font_info.clone();
+
+    macro_rules! drop_keys {
+        ($($field:ident => $key:expr),* $(,)?) => {
+            $(
+                if info.$field.take().is_some() {
+                    losses.push(DataLoss::FontInfo($key));
+                }
+            )*
+        };
+    }
+
+    drop_keys!(
+        guidelines => "guidelines",
+        open_type_gasp_range_records => "openTypeGaspRangeRecords",
+        open_type_name_records => "openTypeNameRecords",
+        open_type_name_wws_family_name => "openTypeNameWWSFamilyName",
+        open_type_name_wws_subfamily_name => "openTypeNameWWSSubfamilyName",
+        woff_major_version => "woffMajorVersion",
+        woff_minor_version => "woffMinorVersion",
+        woff_metadata_unique_id => "woffMetadataUniqueID",
+        woff_metadata_vendor => "woffMetadataVendor",
+        woff_metadata_credits => "woffMetadataCredits",
+        woff_metadata_description => "woffMetadataDescription",
+        woff_metadata_license => "woffMetadataLicense",
+        woff_metadata_copyright => "woffMetadataCopyright",
+        woff_metadata_trademark => "woffMetadataTrademark",
+        woff_metadata_licensee => "woffMetadataLicensee",
+        woff_metadata_extensions => "woffMetadataExtensions",
+    );
+    info
+}
+
+/// Restore the original names of kerning groups that were renamed when
+/// the font was converted to UFO3.
+///
+/// If a restored name clashes with an existing group, the renamed group wins.
+pub(crate) fn kerning_to_v2(
+    kerning: Option<&Kerning>,
+    groups: Option<&Groups>,
+    renames: &KerningGroupRenames,
+) -> (Option<Kerning>, Option<Groups>) {
+    let old_name = |name: &str| {
+        renames.first.get(name).or_else(|| renames.second.get(name)).map(String::as_str)
+    };
+
+    let groups = groups.map(|groups| {
+        let mut kept: BTreeMap<String, Vec<String>> = groups
+            .iter()
+            .filter(|(name, _)| old_name(name).is_none())
+            .map(|(name, glyphs)| (name.clone(), glyphs.clone()))
+            .collect();
+        for (name, glyphs) in groups.iter() {
+            if let Some(old) = old_name(name) {
+                kept.insert(old.to_string(), glyphs.clone());
+            }
+        }
+        Groups::from(kept)
+    });
+
+    let kerning = kerning.map(|kerning| {
+        let mut result = Kerning::new();
+        for (first, second, value) in kerning.iter() {
+            let first = old_name(first).unwrap_or(first);
+            let second = old_name(second).unwrap_or(second);
+            result.insert(first, second, value);
+        }
+        result
+    });
+    (kerning, groups)
+}
+
+/// Returns a copy of `glyph` that can be written as GLIF format 1.
+///
+/// Anchors become single-point contours with a named move point, as they
+/// were stored in UFO2.
+pub(crate) fn glyph_to_v1(glyph: &Glyph, losses: &mut Vec<DataLoss>) -> Glyph {
+    let mut glyph = glyph.clone();
+    glyph.format = GlifVersion::V1;
+    glyph.format_minor = 0;
+    let name = glyph.name.clone();
+    glyph.remove_conversion_marker();
+
+    if glyph.guidelines.take().is_some() {
+        losses.push(DataLoss::GlyphGuidelines(name.clone()));
+    }
+    if glyph.image.take().is_some() {
+        losses.push(DataLoss::GlyphImage(name.clone()));
+    }
+    if glyph.note.take().is_some() {
+        losses.push(DataLoss::GlyphNote(name.clone()));
+    }
+
+    let mut had_identifiers = false;
+    let mut had_anchor_colors = false;
+    if let Some(outline) = glyph.outline.as_mut() {
+        for component in outline.components_mut() {
+            had_identifiers |= component.identifier.take().is_some();
+        }
+        for contour in outline.contours_mut() {
+            had_identifiers |= contour.identifier.take().is_some();
+            for point in contour.points.iter_mut() {
+                had_identifiers |= point.identifier.take().is_some();
+            }
+        }
+    }
+
+    if let Some(anchors) = glyph.anchors.take() {
+        let outline = glyph.outline.get_or_insert_with(Default::default);
+        for anchor in anchors {
+            had_identifiers |= anchor.identifier.is_some();
+            had_anchor_colors |= anchor.color.is_some();
+            let point = ContourPoint {
+                name: anchor.name,
+                x: anchor.x,
+                y: anchor.y,
+                typ: PointType::Move,
+                smooth: false,
+                identifier: None,
+            };
+            outline.push_contour(Contour { identifier: None, points: vec![point] });
+        }
+    }
+
+    if had_identifiers {
+        losses.push(DataLoss::GlyphIdentifiers(name.clone()));
+    }
+    if had_anchor_colors {
+        losses.push(DataLoss::AnchorColors(name));
+    }
+    glyph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::{Anchor, Color, Identifier};
+
+    #[test]
+    fn glyph_v1() {
+        let mut glyph = Glyph::new_named("A");
+        glyph.note = Some("hello".into());
+        glyph.anchors = Some(vec![Anchor {
+            x: 10.,
+            y: 20.,
+            name: Some("top".into()),
+            color: Some(Color { red: 1., green: 0., blue: 0., alpha: 1. }),
+            identifier: Some(Identifier("anchor1".into())),
+        }]);
+
+        let mut losses = Vec::new();
+        let v1 = glyph_to_v1(&glyph, &mut losses);
+        assert_eq!(v1.format, GlifVersion::V1);
+        assert_eq!(v1.anchors, None);
+        assert_eq!(v1.note, None);
+        let contours: Vec<_> = v1.outline.as_ref().unwrap().contours().collect();
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].points[0].name.as_deref(), Some("top"));
+        assert_eq!(contours[0].points[0].typ, PointType::Move);
+        assert_eq!(
+            losses,
+            vec![
+                DataLoss::GlyphNote("A".into()),
+                DataLoss::GlyphIdentifiers("A".into()),
+                DataLoss::AnchorColors("A".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn kerning_v2() {
+        let mut groups = Groups::new();
+        groups.insert("public.kern1.O", vec!["O".into(), "D".into()]);
+        groups.insert("@MMK_L_O", vec!["O".into()]);
+        groups.insert("public.kern2.A", vec!["A".into()]);
+        let mut kerning = Kerning::new();
+        kerning.insert("public.kern1.O", "public.kern2.A", -10.);
+
+        let mut renames = KerningGroupRenames::default();
+        renames.first.insert("public.kern1.O".into(), "@MMK_L_O".into());
+
+        let (kerning, groups) = kerning_to_v2(Some(&kerning), Some(&groups), &renames);
+        let groups = groups.unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups.get("@MMK_L_O").map(Vec::len), Some(2));
+        assert!(groups.contains("public.kern2.A"));
+        assert_eq!(kerning.unwrap().get("@MMK_L_O", "public.kern2.A"), Some(-10.));
+    }
+}
@@ -1,8 +1,11 @@
 //! Errors, errors, errors
 
 use std::io::Error as IoError;
-use std::rc::Rc;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::features::IncludeError;
+use crate::groups::GroupsValidationError;
 use plist::Error as PlistError;
 use quick_xml::Error as XmlError;
 
@@ -15,8 +18,49 @@ pub enum Error {
     MissingFile(&'static str),
     PlistError(PlistError),
     MissingGlyph,
+    /// The font's `metainfo.plist` has a format version that this crate
+    /// cannot read.
+    UnsupportedFormatVersion(u32),
+    /// A value in `fontinfo.plist` is outside the range allowed by the spec.
+    InvalidFontInfo,
+    /// The font's groups break the rules for kerning groups.
+    InvalidGroups(GroupsValidationError),
+    /// A glyph with this name already exists.
+    DuplicateGlyph(String),
+    /// A UFO3 font has no layer stored in the `glyphs` directory.
+    MissingDefaultLayer,
+    /// There is no layer with this name.
+    MissingLayer(String),
+    /// A layer with this name already exists.
+    DuplicateLayer(String),
+    /// The default layer cannot be removed.
+    RemoveDefaultLayer,
+    /// A layer cannot be moved to this position, because there are not that
+    /// many layers.
+    LayerIndexOutOfRange(usize),
+    /// A file in the `images` directory is not a PNG image, or an image was
+    /// given a name that isn't a plain file name.
+    InvalidImage(PathBuf),
+    /// A file in the data store was given a path that isn't relative to the
+    /// `data` directory.
+    InvalidDataPath(PathBuf),
+    /// An `include()` statement in the feature file could not be resolved.
+    FeatureInclude(IncludeError),
     /// A wrapper for stashing errors for later use.
-    SavedError(Rc<Error>),
+    SavedError(Arc<Error>),
+    /// One or more glyphs failed to load while loading a font eagerly.
+    GlyphErrors(Vec<GlyphError>),
+}
+
+/// A glyph that could not be loaded.
+#[derive(Debug)]
+pub struct GlyphError {
+    /// The name of the layer containing the glyph.
+    pub layer: String,
+    /// The name of the glyph.
+    pub glyph: String,
+    /// The reason the glyph could not be loaded.
+    pub error: Error,
 }
 
 #[doc(hidden)]
@@ -69,12 +113,14 @@ pub enum ErrorKind {
     BadGuideline,
     BadComponent,
     BadImage,
+    BadLib,
     UnexpectedDuplicate,
     UnexpectedElement,
     UnexpectedEof,
 }
 
 impl ErrorKind {
+    #[allow(clippy::wrong_self_convention)]
     pub(crate) fn to_error(self, position: usize) -> ParseGlifError {
         ParseGlifError { kind: self, position }
     }
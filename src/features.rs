@@ -0,0 +1,186 @@
+//! Working with the [`features.fea`][features] file.
+//!
+//! [features]: http://unifiedfontobject.org/versions/ufo3/features.fea/
+
+use std::fs;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Feature source with all `include()` statements replaced by the contents
+/// of the files they name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedFeatures {
+    /// The full feature source.
+    pub text: String,
+    /// Every file that was included, directly or indirectly, in the order
+    /// they were first included.
+    pub included_files: Vec<PathBuf>,
+}
+
+/// The reason `include()` statements could not be resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncludeError {
+    /// An included file does not exist.
+    MissingFile(PathBuf),
+    /// A file includes itself, directly or indirectly. The paths are the
+    /// chain of includes, starting and ending with the same file.
+    Cycle(Vec<PathBuf>),
+}
+
+/// Resolve the `include()` statements in `features`, recursively.
+///
+/// Relative paths are resolved against `include_dir`, which should usually
+/// be the directory that contains the UFO; absolute paths are used as is.
+/// Statements inside comments are left alone.
+///
+/// # Examples
+///
+/// ```no_run
+/// use norad::{features, Ufo};
+///
+/// let ufo = Ufo::load("fonts/Rofls.ufo").unwrap();
+/// let source = ufo.features.as_ref().unwrap();
+/// let resolved = features::resolve_includes(source, "fonts").unwrap();
+/// println!("included: {:?}", resolved.included_files);
+/// ```
+pub fn resolve_includes<P: AsRef<Path>>(
+    features: &str,
+    include_dir: P,
+) -> Result<ResolvedFeatures, Error> {
+    let mut resolver =
+        Resolver { include_dir: include_dir.as_ref(), stack: Vec::new(), files: Vec::new() };
+    let text = resolver.resolve(features)?;
+    Ok(ResolvedFeatures { text, included_files: resolver.files })
+}
+
+struct Resolver<'a> {
+    include_dir: &'a Path,
+    /// The chain of files currently being included, for cycle detection.
+    stack: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, source: &str) -> Result<String, Error> {
+        let mut result = String::with_capacity(source.len());
+        let mut rest = source;
+        while let Some(idx) = rest.find(['#', 'i']) {
+            let (before, after) = rest.split_at(idx);
+            result.push_str(before);
+            if after.starts_with('#') {
+                let end = after.find('\n').unwrap_or(after.len());
+                result.push_str(&after[..end]);
+                rest = &after[end..];
+                continue;
+            }
+
+            let at_word_start = !result.chars().next_back().map(is_name_char).unwrap_or(false);
+            match parse_include(after) {
+                Some((path, len)) if at_word_start => {
+                    result.push_str(&self.include(path)?);
+                    rest = &after[len..];
+                }
+                _ => {
+                    result.push('i');
+                    rest = &after[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    fn include(&mut self, path: &str) -> Result<String, Error> {
+        let path = self.include_dir.join(path);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == IoErrorKind::NotFound => {
+                return Err(Error::FeatureInclude(IncludeError::MissingFile(path)));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let canonical = path.canonicalize()?;
+        if let Some(pos) = self.stack.iter().position(|p| p == &canonical) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(canonical);
+            return Err(Error::FeatureInclude(IncludeError::Cycle(cycle)));
+        }
+        if !self.files.contains(&canonical) {
+            self.files.push(canonical.clone());
+        }
+
+        self.stack.push(canonical);
+        let resolved = self.resolve(&text)?;
+        self.stack.pop();
+        Ok(resolved)
+    }
+}
+
+/// If `s` starts with an `include(path)` statement, returns the path and
+/// the length of the statement, including any trailing `;`.
+fn parse_include(s: &str) -> Option<(&str, usize)> {
+    let after_keyword = s.strip_prefix("include")?;
+    let args = after_keyword.trim_start().strip_prefix('(')?;
+    let close = args.find(')')?;
+    let path = args[..close].trim();
+    if path.is_empty() {
+        return None;
+    }
+
+    let after_args = &args[close + 1..];
+    let trimmed = after_args.trim_start();
+    let rest = trimmed.strip_prefix(';').unwrap_or(after_args);
+    Some((path, s.len() - rest.len()))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_statement() {
+        assert_eq!(parse_include("include(a.fea);\nfoo"), Some(("a.fea", 15)));
+        assert_eq!(parse_include("include ( dir/a.fea ) ;"), Some(("dir/a.fea", 23)));
+        assert_eq!(parse_include("include(a.fea) foo"), Some(("a.fea", 14)));
+        assert_eq!(parse_include("include();"), None);
+        assert_eq!(parse_include("includes(a.fea);"), None);
+        assert_eq!(parse_include("ignore sub a;"), None);
+    }
+
+    #[test]
+    fn resolve() {
+        let dir = "testdata/featurestest";
+        let source = "# include(commented.fea);\ninclude(main.fea);\n";
+        let resolved = resolve_includes(source, dir).unwrap();
+        assert_eq!(
+            resolved.text,
+            "# include(commented.fea);\nlanguagesystem DFLT dflt;\n\
+             feature kern {\n    pos A V -40;\n} kern;\n\n\n"
+        );
+        let names: Vec<_> =
+            resolved.included_files.iter().map(|p| p.file_name().unwrap().to_owned()).collect();
+        assert_eq!(names, vec!["main.fea", "kern.fea"]);
+    }
+
+    #[test]
+    fn errors() {
+        let dir = "testdata/featurestest";
+        match resolve_includes("include(cycle1.fea);", dir) {
+            Err(Error::FeatureInclude(IncludeError::Cycle(paths))) => assert_eq!(paths.len(), 3),
+            other => panic!("expected cycle, found {:?}", other),
+        }
+        match resolve_includes("include(missing.fea);", dir) {
+            Err(Error::FeatureInclude(IncludeError::MissingFile(path))) => {
+                assert!(path.ends_with("missing.fea"))
+            }
+            other => panic!("expected missing file, found {:?}", other),
+        }
+    }
+}
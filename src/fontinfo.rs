@@ -0,0 +1,577 @@
+//! The contents of the [`fontinfo.plist`][fontinfo] file.
+//!
+//! [fontinfo]: http://unifiedfontobject.org/versions/ufo3/fontinfo.plist/
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{Deserialize, Deserializer, Error as DeError};
+use serde::ser::{Serialize, Serializer};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::glyph::Guideline;
+use crate::Error;
+
+/// Top-level font information, loaded from `fontinfo.plist`.
+///
+/// All fields are optional. See the [spec] for the meaning of each field.
+/// Keys that are not part of the spec are rejected when loading, rather than
+/// being dropped.
+///
+/// [spec]: http://unifiedfontobject.org/versions/ufo3/fontinfo.plist/
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FontInfo {
+    // Generic identification
+    pub family_name: Option<String>,
+    pub style_name: Option<String>,
+    pub style_map_family_name: Option<String>,
+    pub style_map_style_name: Option<StyleMapStyleName>,
+    pub version_major: Option<i32>,
+    pub version_minor: Option<u32>,
+    /// Deprecated in UFO3, but kept so that it survives a round trip.
+    pub year: Option<i32>,
+
+    // Generic legal
+    pub copyright: Option<String>,
+    pub trademark: Option<String>,
+
+    // Generic dimension
+    pub units_per_em: Option<IntegerOrFloat>,
+    pub descender: Option<IntegerOrFloat>,
+    pub x_height: Option<IntegerOrFloat>,
+    pub cap_height: Option<IntegerOrFloat>,
+    pub ascender: Option<IntegerOrFloat>,
+    pub italic_angle: Option<IntegerOrFloat>,
+
+    // Generic miscellaneous
+    pub note: Option<String>,
+
+    // OpenType gasp table
+    pub open_type_gasp_range_records: Option<Vec<GaspRangeRecord>>,
+
+    // OpenType head table
+    pub open_type_head_created: Option<String>,
+    #[serde(rename = "openTypeHeadLowestRecPPEM")]
+    pub open_type_head_lowest_rec_ppem: Option<u32>,
+    pub open_type_head_flags: Option<Vec<u8>>,
+
+    // OpenType hhea table
+    pub open_type_hhea_ascender: Option<i32>,
+    pub open_type_hhea_descender: Option<i32>,
+    pub open_type_hhea_line_gap: Option<i32>,
+    pub open_type_hhea_caret_slope_rise: Option<i32>,
+    pub open_type_hhea_caret_slope_run: Option<i32>,
+    pub open_type_hhea_caret_offset: Option<i32>,
+
+    // OpenType name table
+    pub open_type_name_designer: Option<String>,
+    #[serde(rename = "openTypeNameDesignerURL")]
+    pub open_type_name_designer_url: Option<String>,
+    pub open_type_name_manufacturer: Option<String>,
+    #[serde(rename = "openTypeNameManufacturerURL")]
+    pub open_type_name_manufacturer_url: Option<String>,
+    pub open_type_name_license: Option<String>,
+    #[serde(rename = "openTypeNameLicenseURL")]
+    pub open_type_name_license_url: Option<String>,
+    pub open_type_name_version: Option<String>,
+    #[serde(rename = "openTypeNameUniqueID")]
+    pub open_type_name_unique_id: Option<String>,
+    pub open_type_name_description: Option<String>,
+    pub open_type_name_preferred_family_name: Option<String>,
+    pub open_type_name_preferred_subfamily_name: Option<String>,
+    pub open_type_name_compatible_full_name: Option<String>,
+    pub open_type_name_sample_text: Option<String>,
+    #[serde(rename = "openTypeNameWWSFamilyName")]
+    pub open_type_name_wws_family_name: Option<String>,
+    #[serde(rename = "openTypeNameWWSSubfamilyName")]
+    pub open_type_name_wws_subfamily_name: Option<String>,
+    pub open_type_name_records: Option<Vec<NameRecord>>,
+
+    // OpenType OS/2 table
+    #[serde(rename = "openTypeOS2WidthClass")]
+    pub open_type_os2_width_class: Option<u8>,
+    #[serde(rename = "openTypeOS2WeightClass")]
+    pub open_type_os2_weight_class: Option<u32>,
+    #[serde(rename = "openTypeOS2Selection")]
+    pub open_type_os2_selection: Option<Vec<u8>>,
+    #[serde(rename = "openTypeOS2VendorID")]
+    pub open_type_os2_vendor_id: Option<String>,
+    #[serde(rename = "openTypeOS2Panose")]
+    pub open_type_os2_panose: Option<Vec<u32>>,
+    #[serde(rename = "openTypeOS2FamilyClass")]
+    pub open_type_os2_family_class: Option<Vec<u8>>,
+    #[serde(rename = "openTypeOS2UnicodeRanges")]
+    pub open_type_os2_unicode_ranges: Option<Vec<u8>>,
+    #[serde(rename = "openTypeOS2CodePageRanges")]
+    pub open_type_os2_code_page_ranges: Option<Vec<u8>>,
+    #[serde(rename = "openTypeOS2TypoAscender")]
+    pub open_type_os2_typo_ascender: Option<i32>,
+    #[serde(rename = "openTypeOS2TypoDescender")]
+    pub open_type_os2_typo_descender: Option<i32>,
+    #[serde(rename = "openTypeOS2TypoLineGap")]
+    pub open_type_os2_typo_line_gap: Option<i32>,
+    #[serde(rename = "openTypeOS2WinAscent")]
+    pub open_type_os2_win_ascent: Option<u32>,
+    #[serde(rename = "openTypeOS2WinDescent")]
+    pub open_type_os2_win_descent: Option<u32>,
+    #[serde(rename = "openTypeOS2Type")]
+    pub open_type_os2_type: Option<Vec<u8>>,
+    #[serde(rename = "openTypeOS2SubscriptXSize")]
+    pub open_type_os2_subscript_x_size: Option<i32>,
+    #[serde(rename = "openTypeOS2SubscriptYSize")]
+    pub open_type_os2_subscript_y_size: Option<i32>,
+    #[serde(rename = "openTypeOS2SubscriptXOffset")]
+    pub open_type_os2_subscript_x_offset: Option<i32>,
+    #[serde(rename = "openTypeOS2SubscriptYOffset")]
+    pub open_type_os2_subscript_y_offset: Option<i32>,
+    #[serde(rename = "openTypeOS2SuperscriptXSize")]
+    pub open_type_os2_superscript_x_size: Option<i32>,
+    #[serde(rename = "openTypeOS2SuperscriptYSize")]
+    pub open_type_os2_superscript_y_size: Option<i32>,
+    #[serde(rename = "openTypeOS2SuperscriptXOffset")]
+    pub open_type_os2_superscript_x_offset: Option<i32>,
+    #[serde(rename = "openTypeOS2SuperscriptYOffset")]
+    pub open_type_os2_superscript_y_offset: Option<i32>,
+    #[serde(rename = "openTypeOS2StrikeoutSize")]
+    pub open_type_os2_strikeout_size: Option<i32>,
+    #[serde(rename = "openTypeOS2StrikeoutPosition")]
+    pub open_type_os2_strikeout_position: Option<i32>,
+
+    // OpenType vhea table
+    pub open_type_vhea_vert_typo_ascender: Option<i32>,
+    pub open_type_vhea_vert_typo_descender: Option<i32>,
+    pub open_type_vhea_vert_typo_line_gap: Option<i32>,
+    pub open_type_vhea_caret_slope_rise: Option<i32>,
+    pub open_type_vhea_caret_slope_run: Option<i32>,
+    pub open_type_vhea_caret_offset: Option<i32>,
+
+    // PostScript specific data
+    pub postscript_font_name: Option<String>,
+    pub postscript_full_name: Option<String>,
+    pub postscript_slant_angle: Option<IntegerOrFloat>,
+    #[serde(rename = "postscriptUniqueID")]
+    pub postscript_unique_id: Option<i32>,
+    pub postscript_underline_thickness: Option<IntegerOrFloat>,
+    pub postscript_underline_position: Option<IntegerOrFloat>,
+    pub postscript_is_fixed_pitch: Option<bool>,
+    pub postscript_blue_values: Option<Vec<IntegerOrFloat>>,
+    pub postscript_other_blues: Option<Vec<IntegerOrFloat>>,
+    pub postscript_family_blues: Option<Vec<IntegerOrFloat>>,
+    pub postscript_family_other_blues: Option<Vec<IntegerOrFloat>>,
+    pub postscript_stem_snap_h: Option<Vec<IntegerOrFloat>>,
+    pub postscript_stem_snap_v: Option<Vec<IntegerOrFloat>>,
+    pub postscript_blue_fuzz: Option<IntegerOrFloat>,
+    pub postscript_blue_shift: Option<IntegerOrFloat>,
+    pub postscript_blue_scale: Option<IntegerOrFloat>,
+    pub postscript_force_bold: Option<bool>,
+    pub postscript_default_width_x: Option<IntegerOrFloat>,
+    pub postscript_nominal_width_x: Option<IntegerOrFloat>,
+    pub postscript_weight_name: Option<String>,
+    pub postscript_default_character: Option<String>,
+    pub postscript_windows_character_set: Option<u8>,
+
+    // Macintosh FOND resource data
+    #[serde(rename = "macintoshFONDFamilyID")]
+    pub macintosh_fond_family_id: Option<i32>,
+    #[serde(rename = "macintoshFONDName")]
+    pub macintosh_fond_name: Option<String>,
+
+    // WOFF data
+    pub woff_major_version: Option<u32>,
+    pub woff_minor_version: Option<u32>,
+    #[serde(rename = "woffMetadataUniqueID")]
+    pub woff_metadata_unique_id: Option<WoffMetadataUniqueId>,
+    pub woff_metadata_vendor: Option<WoffMetadataVendor>,
+    pub woff_metadata_credits: Option<WoffMetadataCredits>,
+    pub woff_metadata_description: Option<WoffMetadataDescription>,
+    pub woff_metadata_license: Option<WoffMetadataLicense>,
+    pub woff_metadata_copyright: Option<WoffMetadataCopyright>,
+    pub woff_metadata_trademark: Option<WoffMetadataTrademark>,
+    pub woff_metadata_licensee: Option<WoffMetadataLicensee>,
+    pub woff_metadata_extensions: Option<Vec<WoffMetadataExtensionRecord>>,
+
+    // Guidelines
+    pub guidelines: Option<Vec<Guideline>>,
+}
+
+/// A number that may be written as either an `<integer>` or a `<real>`.
+///
+/// Values without a fractional part are written as integers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct IntegerOrFloat(f64);
+
+/// The style map style name, used to group fonts into families.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StyleMapStyleName {
+    Regular,
+    Italic,
+    Bold,
+    BoldItalic,
+}
+
+/// A record in the OpenType `gasp` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GaspRangeRecord {
+    #[serde(rename = "rangeMaxPPEM")]
+    pub range_max_ppem: u32,
+    /// The bits to set in the `rangeGaspBehavior` field.
+    #[serde(rename = "rangeGaspBehavior")]
+    pub range_gasp_behavior: Vec<u8>,
+}
+
+/// A record in the OpenType `name` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameRecord {
+    #[serde(rename = "nameID")]
+    pub name_id: u32,
+    #[serde(rename = "platformID")]
+    pub platform_id: u32,
+    #[serde(rename = "encodingID")]
+    pub encoding_id: u32,
+    #[serde(rename = "languageID")]
+    pub language_id: u32,
+    pub string: String,
+}
+
+/// The text direction of a WOFF metadata element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WoffAttributeDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataUniqueId {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataVendor {
+    pub name: String,
+    pub url: Option<String>,
+    pub dir: Option<WoffAttributeDirection>,
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataCredits {
+    pub credits: Vec<WoffMetadataCredit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataCredit {
+    pub name: String,
+    pub url: Option<String>,
+    pub role: Option<String>,
+    pub dir: Option<WoffAttributeDirection>,
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataDescription {
+    pub url: Option<String>,
+    pub text: Vec<WoffMetadataTextRecord>,
+}
+
+/// A localized piece of text, used throughout the WOFF metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataTextRecord {
+    pub text: String,
+    pub language: Option<String>,
+    pub dir: Option<WoffAttributeDirection>,
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataLicense {
+    pub url: Option<String>,
+    pub id: Option<String>,
+    pub text: Option<Vec<WoffMetadataTextRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataCopyright {
+    pub text: Vec<WoffMetadataTextRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataTrademark {
+    pub text: Vec<WoffMetadataTextRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataLicensee {
+    pub name: String,
+    pub dir: Option<WoffAttributeDirection>,
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataExtensionRecord {
+    pub id: Option<String>,
+    pub names: Option<Vec<WoffMetadataTextRecord>>,
+    pub items: Vec<WoffMetadataExtensionItemRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WoffMetadataExtensionItemRecord {
+    pub id: Option<String>,
+    pub names: Vec<WoffMetadataTextRecord>,
+    pub values: Vec<WoffMetadataTextRecord>,
+}
+
+impl FontInfo {
+    /// Check that the values in this `FontInfo` are within the ranges
+    /// described by the spec.
+    pub fn validate(&self) -> Result<(), Error> {
+        fn check(condition: bool) -> Result<(), Error> {
+            if condition {
+                Ok(())
+            } else {
+                Err(Error::InvalidFontInfo)
+            }
+        }
+
+        let non_negative = |v: &Option<IntegerOrFloat>| v.map(|v| v.get() >= 0.).unwrap_or(true);
+        check(non_negative(&self.units_per_em))?;
+        check(non_negative(&self.postscript_blue_scale))?;
+
+        let even_and_at_most = |v: &Option<Vec<IntegerOrFloat>>, max: usize| {
+            v.as_ref().map(|v| v.len() % 2 == 0 && v.len() <= max).unwrap_or(true)
+        };
+        check(even_and_at_most(&self.postscript_blue_values, 14))?;
+        check(even_and_at_most(&self.postscript_other_blues, 10))?;
+        check(even_and_at_most(&self.postscript_family_blues, 14))?;
+        check(even_and_at_most(&self.postscript_family_other_blues, 10))?;
+
+        let at_most = |v: &Option<Vec<IntegerOrFloat>>, max: usize| {
+            v.as_ref().map(|v| v.len() <= max).unwrap_or(true)
+        };
+        check(at_most(&self.postscript_stem_snap_h, 12))?;
+        check(at_most(&self.postscript_stem_snap_v, 12))?;
+
+        check(self.open_type_os2_width_class.map(|c| (1..=9).contains(&c)).unwrap_or(true))?;
+        check(self.open_type_os2_panose.as_ref().map(|v| v.len() == 10).unwrap_or(true))?;
+        check(
+            self.open_type_os2_family_class
+                .as_ref()
+                .map(|v| v.len() == 2 && v[0] <= 14 && v[1] <= 15)
+                .unwrap_or(true),
+        )?;
+        check(
+            self.postscript_windows_character_set.map(|c| (1..=20).contains(&c)).unwrap_or(true),
+        )?;
+        check(self.open_type_head_created.as_ref().map(|s| is_valid_date(s)).unwrap_or(true))?;
+        Ok(())
+    }
+}
+
+/// Returns `true` if `s` has the form `YYYY/MM/DD HH:MM:SS`.
+fn is_valid_date(s: &str) -> bool {
+    s.len() == 19
+        && s.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '/',
+            10 => c == ' ',
+            13 | 16 => c == ':',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+impl IntegerOrFloat {
+    pub fn new(value: f64) -> Self {
+        IntegerOrFloat(value)
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+
+    /// Returns `true` if this value has no fractional part.
+    pub fn is_integer(self) -> bool {
+        self.0.fract() == 0.0 && self.0.abs() < i64::MAX as f64
+    }
+}
+
+impl From<f64> for IntegerOrFloat {
+    fn from(src: f64) -> IntegerOrFloat {
+        IntegerOrFloat(src)
+    }
+}
+
+impl From<i32> for IntegerOrFloat {
+    fn from(src: i32) -> IntegerOrFloat {
+        IntegerOrFloat(src.into())
+    }
+}
+
+impl Serialize for IntegerOrFloat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            serializer.serialize_i64(self.0 as i64)
+        } else {
+            serializer.serialize_f64(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IntegerOrFloat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(IntegerOrFloat)
+    }
+}
+
+impl StyleMapStyleName {
+    fn as_str(self) -> &'static str {
+        match self {
+            StyleMapStyleName::Regular => "regular",
+            StyleMapStyleName::Italic => "italic",
+            StyleMapStyleName::Bold => "bold",
+            StyleMapStyleName::BoldItalic => "bold italic",
+        }
+    }
+}
+
+impl FromStr for StyleMapStyleName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "regular" => Ok(StyleMapStyleName::Regular),
+            "italic" => Ok(StyleMapStyleName::Italic),
+            "bold" => Ok(StyleMapStyleName::Bold),
+            "bold italic" => Ok(StyleMapStyleName::BoldItalic),
+            other => Err(format!("unknown styleMapStyleName '{}'", other)),
+        }
+    }
+}
+
+impl WoffAttributeDirection {
+    fn as_str(self) -> &'static str {
+        match self {
+            WoffAttributeDirection::LeftToRight => "ltr",
+            WoffAttributeDirection::RightToLeft => "rtl",
+        }
+    }
+}
+
+impl FromStr for WoffAttributeDirection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ltr" => Ok(WoffAttributeDirection::LeftToRight),
+            "rtl" => Ok(WoffAttributeDirection::RightToLeft),
+            other => Err(format!("unknown text direction '{}'", other)),
+        }
+    }
+}
+
+/// Implement serde traits for an enum that is stored as a plist string.
+macro_rules! string_enum_serde {
+    ($name:ident) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+string_enum_serde!(StyleMapStyleName);
+string_enum_serde!(WoffAttributeDirection);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::Line;
+
+    #[test]
+    fn load_fontinfo() {
+        let path = "testdata/fontinfotest.ufo/fontinfo.plist";
+        let font_info: FontInfo = plist::from_file(path).expect("failed to load fontinfo");
+        assert_eq!(font_info.family_name.as_deref(), Some("Some Font (Family Name)"));
+        assert_eq!(font_info.style_map_style_name, Some(StyleMapStyleName::BoldItalic));
+        assert_eq!(font_info.units_per_em, Some(1000.into()));
+        assert_eq!(font_info.italic_angle, Some((-12.5).into()));
+        assert_eq!(font_info.open_type_os2_panose.as_ref().map(Vec::len), Some(10));
+        assert_eq!(
+            font_info.open_type_name_designer_url.as_ref().unwrap(),
+            "http://somedesigner.com"
+        );
+
+        let records = font_info.open_type_name_records.as_ref().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].name_id, 9);
+        assert_eq!(records[1].string, "Some Designer");
+
+        let guidelines = font_info.guidelines.as_ref().unwrap();
+        assert_eq!(guidelines[0].line, Line::Horizontal(200.));
+        assert_eq!(guidelines[1].line, Line::Angle { x: 100., y: 0., degrees: 45. });
+        assert!(guidelines[1].color.is_some());
+
+        let vendor = font_info.woff_metadata_vendor.as_ref().unwrap();
+        assert_eq!(vendor.dir, Some(WoffAttributeDirection::RightToLeft));
+        let extensions = font_info.woff_metadata_extensions.as_ref().unwrap();
+        assert_eq!(extensions[0].items[0].values[0].text, "Value Text");
+        font_info.validate().unwrap();
+    }
+
+    #[test]
+    fn fontinfo_round_trip() {
+        let path = "testdata/fontinfotest.ufo/fontinfo.plist";
+        let font_info: FontInfo = plist::from_file(path).unwrap();
+        let mut buf = Vec::new();
+        plist::to_writer_xml(&mut buf, &font_info).unwrap();
+        let font_info2: FontInfo = plist::from_reader_xml(buf.as_slice()).unwrap();
+        assert_eq!(font_info, font_info2);
+
+        let written = String::from_utf8(buf).unwrap();
+        assert!(written.contains("<integer>1000</integer>"));
+        assert!(written.contains("<real>-12.5</real>"));
+        assert!(written.contains("<string>bold italic</string>"));
+    }
+
+    #[test]
+    fn year_and_unknown_keys() {
+        let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>year</key>
+    <integer>2019</integer>
+</dict>
+</plist>"#;
+        let font_info: FontInfo = plist::from_reader_xml(data.as_bytes()).unwrap();
+        assert_eq!(font_info.year, Some(2019));
+        let mut buf = Vec::new();
+        plist::to_writer_xml(&mut buf, &font_info).unwrap();
+        assert!(String::from_utf8(buf).unwrap().contains("<key>year</key>"));
+
+        let data = data.replace("<key>year</key>", "<key>yaer</key>");
+        assert!(plist::from_reader_xml::<_, FontInfo>(data.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn validation() {
+        let mut font_info = FontInfo::default();
+        font_info.validate().unwrap();
+        font_info.open_type_os2_width_class = Some(10);
+        assert!(font_info.validate().is_err());
+        font_info.open_type_os2_width_class = Some(5);
+        font_info.postscript_blue_values = Some(vec![1.into()]);
+        assert!(font_info.validate().is_err());
+        font_info.postscript_blue_values = None;
+        font_info.open_type_head_created = Some("2019/05/22 12:30".into());
+        assert!(font_info.validate().is_err());
+    }
+}
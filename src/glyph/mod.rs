@@ -1,15 +1,27 @@
 //! Data related to individual glyphs.
 
 mod parse;
+pub(crate) mod serde_impls;
 mod serialize;
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests;
 
-use crate::Error;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-//FIXME: actually load the 'lib' data
-type Plist = ();
+use plist::Value;
+
+use crate::Error;
+
+/// A lib dictionary, mapping keys to arbitrary property list values.
+pub type Plist = BTreeMap<String, Value>;
+
+static PUBLIC_MARK_COLOR_KEY: &str = "public.markColor";
+static PUBLIC_VERTICAL_ORIGIN_KEY: &str = "public.verticalOrigin";
+static PUBLIC_OBJECT_LIBS_KEY: &str = "public.objectLibs";
+/// The lib key recording that a glyph was converted from GLIF format 1.
+pub(crate) static CONVERTED_FROM_V1_KEY: &str = "org.linebender.norad.convertedFromGlifVersion1";
 
 /// A glyph, loaded from a [.glif file][glif].
 ///
@@ -18,6 +30,11 @@ type Plist = ();
 pub struct Glyph {
     pub name: String,
     pub format: GlifVersion,
+    /// The `formatMinor` attribute of the glyph. Files with a minor version
+    /// newer than this crate knows about are loaded anyway, and the version
+    /// is written back unchanged; see
+    /// [`has_newer_format_minor`](#method.has_newer_format_minor).
+    pub format_minor: u32,
     pub advance: Option<Advance>,
     pub codepoints: Option<Vec<char>>,
     pub note: Option<String>,
@@ -29,12 +46,84 @@ pub struct Glyph {
 }
 
 impl Glyph {
+    /// Load the `.glif` file at `path`.
+    ///
+    /// Glyphs in GLIF format 1 are converted to the format 2 model; see
+    /// [`convert_to_v2`](#method.convert_to_v2).
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut glyph = Glyph::load_raw(path)?;
+        glyph.convert_to_v2();
+        Ok(glyph)
+    }
+
+    /// Load the `.glif` file at `path`, keeping GLIF format 1 data as it was
+    /// written.
+    pub fn load_raw<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let data = std::fs::read(path.as_ref())?;
         parse::parse_glyph(&data)
     }
 
-    #[doc(hidden)]
+    /// Returns `true` if the lib records that this glyph was converted from
+    /// GLIF format 1.
+    pub(crate) fn is_converted(&self) -> bool {
+        self.lib.as_ref().map(|lib| lib.contains_key(CONVERTED_FROM_V1_KEY)).unwrap_or(false)
+    }
+
+    /// Remove the record of a GLIF format 1 conversion from the lib, and the
+    /// lib itself if nothing else is left in it.
+    pub(crate) fn remove_conversion_marker(&mut self) {
+        if let Some(lib) = self.lib.as_mut() {
+            lib.remove(CONVERTED_FROM_V1_KEY);
+            if lib.is_empty() {
+                self.lib = None;
+            }
+        }
+    }
+
+    /// Convert a glyph loaded from GLIF format 1 to the format 2 model.
+    ///
+    /// In format 1, anchors were stored as contours with a single, named
+    /// `move` point; these contours are removed from the outline and added
+    /// to the glyph's anchors. The conversion is recorded in the glyph's lib,
+    /// under `org.linebender.norad.convertedFromGlifVersion1`; this key is
+    /// removed again when the glyph is saved as part of a layer. Glyphs in
+    /// format 2 are left unchanged.
+    pub fn convert_to_v2(&mut self) {
+        if self.format != GlifVersion::V1 {
+            return;
+        }
+        self.format = GlifVersion::V2;
+        self.format_minor = 0;
+        self.lib
+            .get_or_insert_with(Plist::new)
+            .insert(CONVERTED_FROM_V1_KEY.into(), Value::Boolean(true));
+
+        let outline = match self.outline.as_mut() {
+            Some(outline) => outline,
+            None => return,
+        };
+        let anchors = outline.take_contours(Contour::is_v1_anchor);
+        if outline.elements.is_empty() {
+            self.outline = None;
+        }
+
+        if !anchors.is_empty() {
+            let new_anchors = anchors.into_iter().map(|mut contour| {
+                let point = contour.points.remove(0);
+                Anchor { x: point.x, y: point.y, name: point.name, color: None, identifier: None }
+            });
+            self.anchors.get_or_insert_with(Vec::new).extend(new_anchors);
+        }
+    }
+
+    /// Returns `true` if this glyph's `formatMinor` is newer than the
+    /// [latest minor version](enum.GlifVersion.html#method.latest_minor)
+    /// this crate understands, so it may contain data that was not read.
+    pub fn has_newer_format_minor(&self) -> bool {
+        self.format_minor > self.format.latest_minor()
+    }
+
+    /// Write this glyph as a `.glif` file at `path`, replacing any existing file.
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
         let data = self.encode_xml()?;
         std::fs::write(path, &data)?;
@@ -50,6 +139,7 @@ impl Glyph {
         Glyph {
             name,
             format,
+            format_minor: 0,
             advance: None,
             codepoints: None,
             note: None,
@@ -60,6 +150,73 @@ impl Glyph {
             lib: None,
         }
     }
+
+    /// Returns the value of the `public.markColor` lib key, if it is present
+    /// and valid.
+    pub fn mark_color(&self) -> Option<Color> {
+        self.lib_value(PUBLIC_MARK_COLOR_KEY)
+            .and_then(Value::as_string)
+            .and_then(|s| s.parse().ok())
+    }
+
+    /// Set or remove the `public.markColor` lib key.
+    pub fn set_mark_color(&mut self, color: Option<Color>) {
+        self.set_lib_value(PUBLIC_MARK_COLOR_KEY, color.map(|c| Value::String(c.to_rgba_string())));
+    }
+
+    /// Returns the value of the `public.verticalOrigin` lib key, if it is
+    /// present and a number.
+    pub fn vertical_origin(&self) -> Option<f64> {
+        self.lib_value(PUBLIC_VERTICAL_ORIGIN_KEY).and_then(|v| match v {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Real(r) => Some(*r),
+            _ => None,
+        })
+    }
+
+    /// Set or remove the `public.verticalOrigin` lib key.
+    pub fn set_vertical_origin(&mut self, origin: Option<f64>) {
+        let value =
+            origin.map(
+                |v| {
+                    if v.fract() == 0.0 {
+                        Value::Integer(v as i64)
+                    } else {
+                        Value::Real(v)
+                    }
+                },
+            );
+        self.set_lib_value(PUBLIC_VERTICAL_ORIGIN_KEY, value);
+    }
+
+    /// Returns the lib of the object with this identifier, from the
+    /// `public.objectLibs` lib key.
+    pub fn object_lib(&self, identifier: &str) -> Option<&Plist> {
+        self.lib_value(PUBLIC_OBJECT_LIBS_KEY)
+            .and_then(Value::as_dictionary)
+            .and_then(|libs| libs.get(identifier))
+            .and_then(Value::as_dictionary)
+    }
+
+    fn lib_value(&self, key: &str) -> Option<&Value> {
+        self.lib.as_ref().and_then(|lib| lib.get(key))
+    }
+
+    fn set_lib_value(&mut self, key: &str, value: Option<Value>) {
+        match value {
+            Some(value) => {
+                self.lib.get_or_insert_with(Plist::new).insert(key.to_string(), value);
+            }
+            None => {
+                if let Some(lib) = self.lib.as_mut() {
+                    lib.remove(key);
+                    if lib.is_empty() {
+                        self.lib = None;
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -68,6 +225,17 @@ pub enum GlifVersion {
     V2 = 2,
 }
 
+impl GlifVersion {
+    /// The newest `formatMinor` of this format version that this crate
+    /// understands.
+    pub fn latest_minor(&self) -> u32 {
+        match self {
+            GlifVersion::V1 => 0,
+            GlifVersion::V2 => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Advance {
     Width(f32),
@@ -117,10 +285,83 @@ pub struct Anchor {
     pub identifier: Option<Identifier>,
 }
 
+/// The contours and components of a glyph.
+///
+/// Elements are stored in a single list, in the order they appear in the
+/// `.glif` file, and are written back out in the same order.
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Outline {
-    pub components: Vec<Component>,
-    pub contours: Vec<Contour>,
+    pub elements: Vec<OutlineElement>,
+}
+
+/// A single element of an [`Outline`](struct.Outline.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutlineElement {
+    Contour(Contour),
+    Component(Component),
+}
+
+impl Outline {
+    /// Returns an iterator over the contours of this outline, in order.
+    pub fn contours(&self) -> impl Iterator<Item = &Contour> {
+        self.elements.iter().filter_map(|element| match element {
+            OutlineElement::Contour(contour) => Some(contour),
+            OutlineElement::Component(_) => None,
+        })
+    }
+
+    /// Returns an iterator over mutable references to the contours of this
+    /// outline, in order.
+    pub fn contours_mut(&mut self) -> impl Iterator<Item = &mut Contour> {
+        self.elements.iter_mut().filter_map(|element| match element {
+            OutlineElement::Contour(contour) => Some(contour),
+            OutlineElement::Component(_) => None,
+        })
+    }
+
+    /// Returns an iterator over the components of this outline, in order.
+    pub fn components(&self) -> impl Iterator<Item = &Component> {
+        self.elements.iter().filter_map(|element| match element {
+            OutlineElement::Component(component) => Some(component),
+            OutlineElement::Contour(_) => None,
+        })
+    }
+
+    /// Returns an iterator over mutable references to the components of this
+    /// outline, in order.
+    pub fn components_mut(&mut self) -> impl Iterator<Item = &mut Component> {
+        self.elements.iter_mut().filter_map(|element| match element {
+            OutlineElement::Component(component) => Some(component),
+            OutlineElement::Contour(_) => None,
+        })
+    }
+
+    /// Add a contour after the existing elements.
+    pub fn push_contour(&mut self, contour: Contour) {
+        self.elements.push(OutlineElement::Contour(contour));
+    }
+
+    /// Add a component after the existing elements.
+    pub fn push_component(&mut self, component: Component) {
+        self.elements.push(OutlineElement::Component(component));
+    }
+
+    /// Remove and return the contours matching `predicate`, keeping the
+    /// order of the remaining elements.
+    pub(crate) fn take_contours<F>(&mut self, mut predicate: F) -> Vec<Contour>
+    where
+        F: FnMut(&Contour) -> bool,
+    {
+        let mut taken = Vec::new();
+        let elements = std::mem::take(&mut self.elements);
+        for element in elements {
+            match element {
+                OutlineElement::Contour(contour) if predicate(&contour) => taken.push(contour),
+                other => self.elements.push(other),
+            }
+        }
+        taken
+    }
 }
 
 /// Another glyph inserted as part of the outline.
@@ -138,6 +379,17 @@ pub struct Contour {
     pub points: Vec<ContourPoint>,
 }
 
+impl Contour {
+    /// Returns `true` if this is how GLIF format 1 stores an anchor: a single,
+    /// named `move` point.
+    fn is_v1_anchor(&self) -> bool {
+        match self.points.as_slice() {
+            [point] => point.typ == PointType::Move && point.name.is_some(),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ContourPoint {
     pub name: Option<String>,
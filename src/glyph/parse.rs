@@ -31,17 +31,25 @@ impl GlifParser {
 
         let glyph = start(&mut reader, &mut buf)?;
         let this = GlifParser(glyph);
-        this.parse_body(&mut reader, &mut buf)
+        this.parse_body(xml, &mut reader, &mut buf)
     }
 
-    fn parse_body(mut self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<Glyph, Error> {
+    fn parse_body(
+        mut self,
+        xml: &[u8],
+        reader: &mut Reader<&[u8]>,
+        buf: &mut Vec<u8>,
+    ) -> Result<Glyph, Error> {
         loop {
             match reader.read_event(buf)? {
+                Event::Empty(ref start) if start.name() == b"lib" => {
+                    self.parse_empty_lib(reader)?
+                }
                 Event::Start(start) | Event::Empty(start) => {
-                    let tag_name = reader.decode(&start.name());
+                    let tag_name = reader.decode(start.name());
                     match tag_name.borrow() {
                         "outline" => self.parse_outline(reader, buf)?,
-                        "lib" => self.parse_lib(reader, buf)?, // do this at some point?
+                        "lib" => self.parse_lib(xml, reader, buf)?,
                         "note" => self.parse_note(reader, buf)?,
                         "advance" => self.parse_advance(reader, start)?,
                         "unicode" => self.parse_unicode(reader, start)?,
@@ -67,12 +75,12 @@ impl GlifParser {
             return Err(err!(reader, ErrorKind::UnexpectedDuplicate))?;
         }
 
-        self.0.outline = Some(Outline { components: Vec::new(), contours: Vec::new() });
+        self.0.outline = Some(Outline::default());
 
         loop {
             match reader.read_event(buf)? {
                 Event::Start(start) | Event::Empty(start) => {
-                    let tag_name = reader.decode(&start.name());
+                    let tag_name = reader.decode(start.name());
                     let mut new_buf = Vec::new(); // borrowck :/
                     match tag_name.borrow() {
                         "contour" => self.parse_contour(start, reader, &mut new_buf)?,
@@ -114,7 +122,7 @@ impl GlifParser {
                 _other => return Err(err!(reader, ErrorKind::UnexpectedElement))?,
             }
         }
-        self.0.outline.as_mut().unwrap().contours.push(Contour { identifier, points });
+        self.0.outline.as_mut().unwrap().push_contour(Contour { identifier, points });
         Ok(())
     }
 
@@ -169,28 +177,60 @@ impl GlifParser {
         }
 
         let component = Component { base: base.unwrap(), transform, identifier };
-        self.0.outline.as_mut().unwrap().components.push(component);
+        self.0.outline.as_mut().unwrap().push_component(component);
         Ok(())
     }
 
-    fn parse_lib(&mut self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<(), Error> {
+    /// Parse the plist dictionary inside a `<lib>` element.
+    ///
+    /// The raw bytes of the element's contents are handed to the plist parser.
+    fn parse_lib(
+        &mut self,
+        xml: &[u8],
+        reader: &mut Reader<&[u8]>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        if self.0.lib.is_some() {
+            return Err(err!(reader, ErrorKind::UnexpectedDuplicate).into());
+        }
+
+        let start = reader.buffer_position();
+        let mut end = start;
+        let mut depth = 0;
         loop {
             match reader.read_event(buf)? {
-                Event::End(ref end) if end.name() == b"lib" => break,
-                Event::Eof => return Err(err!(reader, ErrorKind::UnexpectedEof))?,
+                Event::End(ref end) if end.name() == b"lib" && depth == 0 => break,
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth -= 1,
+                Event::Eof => return Err(err!(reader, ErrorKind::UnexpectedEof).into()),
                 _other => (),
             }
+            end = reader.buffer_position();
+        }
+
+        let value = plist::Value::from_reader_xml(&xml[start..end])?;
+        match value {
+            plist::Value::Dictionary(lib) => self.0.lib = Some(lib),
+            _other => return Err(err!(reader, ErrorKind::BadLib).into()),
         }
         Ok(())
     }
 
+    /// A self-closing `<lib/>` is an empty lib.
+    fn parse_empty_lib(&mut self, reader: &Reader<&[u8]>) -> Result<(), Error> {
+        if self.0.lib.is_some() {
+            return Err(err!(reader, ErrorKind::UnexpectedDuplicate).into());
+        }
+        self.0.lib = Some(Plist::new());
+        Ok(())
+    }
+
     fn parse_note(&mut self, reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<(), Error> {
         loop {
             match reader.read_event(buf)? {
                 Event::End(ref end) if end.name() == b"note" => break,
                 Event::Text(text) => {
-                    self.0.note = Some(text.unescape_and_decode(reader)?);
-                    break;
+                    self.0.note = Some(dedent_note(&text.unescape_and_decode(reader)?));
                 }
                 Event::Eof => return Err(err!(reader, ErrorKind::UnexpectedEof))?,
                 _other => (),
@@ -439,6 +479,28 @@ impl GlifParser {
     }
 }
 
+/// Remove the indentation that is shared by all lines of a note after the
+/// first, which has already been trimmed.
+fn dedent_note(note: &str) -> String {
+    let mut lines = note.lines();
+    let first = lines.next().unwrap_or_default();
+    let rest: Vec<&str> = lines.collect();
+    let indent = rest
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().take_while(|c| c.is_whitespace()).count())
+        .min()
+        .unwrap_or(0);
+    let mut result = first.to_string();
+    for line in rest {
+        result.push('\n');
+        if !line.trim().is_empty() {
+            result.extend(line.chars().skip(indent));
+        }
+    }
+    result
+}
+
 fn start(reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<Glyph, Error> {
     loop {
         match reader.read_event(buf) {
@@ -446,10 +508,11 @@ fn start(reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<Glyph, Error>
             Ok(Event::Start(ref start)) if start.name() == b"glyph" => {
                 let mut name = String::new();
                 let mut format: Option<GlifVersion> = None;
+                let mut format_minor = 0;
                 for attr in start.attributes() {
                     let attr = attr?;
                     if attr.key == b"name" {
-                        name = attr.unescape_and_decode_value(&reader)?;
+                        name = attr.unescape_and_decode_value(reader)?;
                     } else if attr.key == b"format" {
                         let value = attr.unescaped_value()?;
                         let value = reader.decode(&value);
@@ -458,14 +521,24 @@ fn start(reader: &mut Reader<&[u8]>, buf: &mut Vec<u8>) -> Result<Glyph, Error>
                                 .parse()
                                 .map_err(|e: ErrorKind| e.to_error(reader.buffer_position()))?,
                         );
+                    } else if attr.key == b"formatMinor" {
+                        let value = attr.unescaped_value()?;
+                        format_minor = reader
+                            .decode(&value)
+                            .parse()
+                            .map_err(|_| err!(reader, ErrorKind::UnsupportedGlifVersion))?;
                     }
                 }
-                if !name.is_empty() && format.is_some() {
-                    return Ok(Glyph::new(name, format.take().unwrap()));
-                } else {
-                    eprintln!("name '{}', format {:?}", name, format);
-                    return Err(err!(reader, ErrorKind::WrongFirstElement))?;
-                }
+                let format = match format {
+                    Some(format) if !name.is_empty() => format,
+                    _ => {
+                        eprintln!("name '{}', format {:?}", name, format);
+                        return Err(err!(reader, ErrorKind::WrongFirstElement).into());
+                    }
+                };
+                let mut glyph = Glyph::new(name, format);
+                glyph.format_minor = format_minor;
+                return Ok(glyph);
             }
             Ok(_other) => {
                 eprintln!("breaking for {:?}", _other);
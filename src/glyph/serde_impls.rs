@@ -0,0 +1,206 @@
+//! Serde support for glyph types that also appear in property lists,
+//! such as the guidelines in `fontinfo.plist`, and for lib dictionaries.
+
+use std::fmt;
+
+use plist::Value;
+use serde::de::{Deserialize, Deserializer, Error as DeError, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use serde_derive::{Deserialize, Serialize};
+
+use super::{Color, Guideline, Identifier, Line, Plist};
+
+/// The plist representation of a guideline.
+#[derive(Serialize, Deserialize)]
+struct RawGuideline {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    angle: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<Identifier>,
+}
+
+impl Serialize for Guideline {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (x, y, angle) = match self.line {
+            Line::Vertical(x) => (Some(x), None, None),
+            Line::Horizontal(y) => (None, Some(y), None),
+            Line::Angle { x, y, degrees } => (Some(x), Some(y), Some(degrees)),
+        };
+        let raw = RawGuideline {
+            x,
+            y,
+            angle,
+            name: self.name.clone(),
+            color: self.color.clone(),
+            identifier: self.identifier.clone(),
+        };
+        raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Guideline {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawGuideline::deserialize(deserializer)?;
+        let line = match (raw.x, raw.y, raw.angle) {
+            (Some(x), None, None) => Line::Vertical(x),
+            (None, Some(y), None) => Line::Horizontal(y),
+            (Some(x), Some(y), Some(degrees)) => Line::Angle { x, y, degrees },
+            _ => return Err(D::Error::custom("invalid guideline coordinates")),
+        };
+        Ok(Guideline { line, name: raw.name, color: raw.color, identifier: raw.identifier })
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_rgba_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(|_| D::Error::custom("invalid color string"))
+    }
+}
+
+impl Serialize for Identifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Identifier)
+    }
+}
+
+/// Serde support for an optional lib dictionary, for use with
+/// `#[serde(with = "...")]`.
+///
+/// The plist deserializer hands dates to us as strings, so dates in a lib
+/// are read back as strings.
+pub(crate) mod optional_lib {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        lib: &Option<Plist>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match lib {
+            Some(lib) => serializer.serialize_some(&DictRef(lib)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Plist>, D::Error> {
+        match Option::<OwnedValue>::deserialize(deserializer)? {
+            Some(OwnedValue(Value::Dictionary(lib))) => Ok(Some(lib)),
+            Some(_other) => Err(D::Error::custom("lib must be a dictionary")),
+            None => Ok(None),
+        }
+    }
+}
+
+struct DictRef<'a>(&'a Plist);
+
+struct ValueRef<'a>(&'a Value);
+
+/// A plist value read through serde.
+struct OwnedValue(Value);
+
+impl Serialize for DictRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, ValueRef(value))))
+    }
+}
+
+impl Serialize for ValueRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Array(array) => serializer.collect_seq(array.iter().map(ValueRef)),
+            Value::Dictionary(dict) => DictRef(dict).serialize(serializer),
+            Value::Boolean(b) => serializer.serialize_bool(*b),
+            Value::Data(data) => serializer.serialize_bytes(data),
+            Value::Date(date) => date.serialize(serializer),
+            Value::Real(real) => serializer.serialize_f64(*real),
+            Value::Integer(int) => serializer.serialize_i64(*int),
+            Value::String(string) => serializer.serialize_str(string),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OwnedValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor).map(OwnedValue)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a property list value")
+    }
+
+    fn visit_bool<E: DeError>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E: DeError>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u64<E: DeError>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Integer(v as i64))
+    }
+
+    fn visit_f64<E: DeError>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Real(v))
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E: DeError>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Data(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: DeError>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Data(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::new();
+        while let Some(OwnedValue(value)) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut dict = Plist::new();
+        while let Some((key, OwnedValue(value))) = map.next_entry::<String, OwnedValue>()? {
+            dict.insert(key, value);
+        }
+        Ok(Value::Dictionary(dict))
+    }
+}
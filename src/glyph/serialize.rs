@@ -1,27 +1,34 @@
 //! Writing out .glif files
 
-use std::io::{Cursor, Write};
+use std::io::Write;
 
 use quick_xml::{
-    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    events::{BytesEnd, BytesStart, BytesText, Event},
     Error as XmlError, Writer,
 };
 
 use super::{
-    Advance, Anchor, Color, Component, Contour, ContourPoint, GlifVersion, Glyph, Guideline,
-    Identifier, Image, Line, PointType,
+    Advance, AffineTransform, Anchor, Color, Component, Contour, ContourPoint, GlifVersion, Glyph,
+    Guideline, Identifier, Image, Line, OutlineElement, Plist, PointType,
 };
+use crate::write::{self, format_number, push_attribute};
 
 impl Glyph {
+    /// Serialize this glyph in the format produced by ufoNormalizer.
+    ///
+    /// Default values are omitted, and an outline without any contours or
+    /// components is not written.
     pub(crate) fn encode_xml(&self) -> Result<Vec<u8>, XmlError> {
-        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
-        writer.write_event(Event::Decl(BytesDecl::new(b"1.1", Some(b"UTF-8"), None)))?;
+        let mut writer = write::new_writer()?;
         let mut start = BytesStart::borrowed_name(b"glyph");
-        start.push_attribute(("name", self.name.as_str()));
-        start.push_attribute(("format", self.format.as_str()));
+        push_attribute(&mut start, "name", &self.name);
+        push_attribute(&mut start, "format", self.format.as_str());
+        if self.format_minor != 0 {
+            push_attribute(&mut start, "formatMinor", &self.format_minor.to_string());
+        }
         writer.write_event(Event::Start(start))?;
 
-        if let Some(event) = self.advance.as_ref().map(Advance::to_event) {
+        if let Some(event) = self.advance.as_ref().and_then(Advance::to_event) {
             writer.write_event(event)?;
         }
 
@@ -31,12 +38,16 @@ impl Glyph {
             }
         }
 
-        if let Some(ref note) = self.note {
+        if let Some(note) = self.note.as_ref().filter(|note| !note.trim().is_empty()) {
             writer.write_event(Event::Start(BytesStart::borrowed_name(b"note")))?;
-            writer.write_event(Event::Text(BytesText::from_plain_str(note)))?;
+            writer.write_event(Event::Text(BytesText::from_escaped_str(note_text(note))))?;
             writer.write_event(Event::End(BytesEnd::borrowed(b"note")))?;
         }
 
+        if let Some(ref image) = self.image {
+            writer.write_event(image.to_event())?;
+        }
+
         if let Some(guides) = self.guidelines.as_ref() {
             for guide in guides.iter() {
                 writer.write_event(guide.to_event())?;
@@ -49,25 +60,50 @@ impl Glyph {
             }
         }
 
-        if let Some(ref outline) = self.outline {
+        let elements = self.outline.iter().flat_map(|outline| outline.elements.iter());
+        let mut elements = elements
+            .filter(|element| match element {
+                OutlineElement::Contour(contour) => !contour.points.is_empty(),
+                OutlineElement::Component(_) => true,
+            })
+            .peekable();
+        if elements.peek().is_some() {
             writer.write_event(Event::Start(BytesStart::borrowed_name(b"outline")))?;
-            for component in &outline.components {
-                writer.write_event(component.to_event())?;
-            }
-
-            for contour in &outline.contours {
-                contour.write_xml(&mut writer)?;
+            for element in elements {
+                match element {
+                    OutlineElement::Component(component) => {
+                        writer.write_event(component.to_event())?;
+                    }
+                    OutlineElement::Contour(contour) => contour.write_xml(&mut writer)?,
+                }
             }
             writer.write_event(Event::End(BytesEnd::borrowed(b"outline")))?;
         }
 
-        if let Some(ref image) = self.image {
-            writer.write_event(image.to_event())?;
+        if let Some(ref lib) = self.lib {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"lib")))?;
+            write_lib(lib, &mut writer)?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"lib")))?;
         }
+
         writer.write_event(Event::End(BytesEnd::borrowed(b"glyph")))?;
+        write::finish(writer)
+    }
+}
 
-        Ok(writer.into_inner().into_inner())
+/// The escaped contents of a `<note>` element: each line on its own, indented
+/// one level deeper than the element.
+fn note_text(note: &str) -> String {
+    let mut text = String::from("\n");
+    for line in note.trim().lines() {
+        if !line.trim().is_empty() {
+            text.push_str("\t\t");
+            text.push_str(&write::escape_text(line));
+        }
+        text.push('\n');
     }
+    text.push('\t');
+    text
 }
 
 impl GlifVersion {
@@ -80,18 +116,21 @@ impl GlifVersion {
 }
 
 impl Advance {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Option<Event<'_>> {
         let mut start = BytesStart::borrowed_name(b"advance");
-        match self {
-            Advance::Width(w) => start.push_attribute(("width", w.to_string().as_str())),
-            Advance::Height(h) => start.push_attribute(("height", h.to_string().as_str())),
+        match *self {
+            Advance::Width(w) if w != 0. => push_attribute(&mut start, "width", &format_number(w)),
+            Advance::Height(h) if h != 0. => {
+                push_attribute(&mut start, "height", &format_number(h))
+            }
+            _ => return None,
         }
-        Event::Empty(start)
+        Some(Event::Empty(start))
     }
 }
 
 impl Guideline {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Event<'_> {
         let mut start = BytesStart::borrowed_name(b"guideline");
         let (x, y, angle) = match self.line {
             Line::Vertical(x) => (Some(x), None, None),
@@ -99,60 +138,54 @@ impl Guideline {
             Line::Angle { x, y, degrees } => (Some(x), Some(y), Some(degrees)),
         };
 
-        x.map(|x| start.push_attribute(("x", x.to_string().as_str())));
-        y.map(|y| start.push_attribute(("y", y.to_string().as_str())));
-        angle.map(|angle| start.push_attribute(("angle", angle.to_string().as_str())));
-
         if let Some(name) = &self.name {
-            start.push_attribute(("name", name.as_str()));
+            push_attribute(&mut start, "name", name);
         }
-
-        if let Some(Identifier(id)) = &self.identifier {
-            start.push_attribute(("identifier", id.as_str()));
+        if let Some(x) = x {
+            push_attribute(&mut start, "x", &format_number(x));
+        }
+        if let Some(y) = y {
+            push_attribute(&mut start, "y", &format_number(y));
+        }
+        if let Some(angle) = angle {
+            push_attribute(&mut start, "angle", &format_number(angle));
         }
-
         if let Some(color) = &self.color {
-            start.push_attribute(("color", color.to_rgba_string().as_str()));
+            push_attribute(&mut start, "color", &color.to_rgba_string());
+        }
+        if let Some(Identifier(id)) = &self.identifier {
+            push_attribute(&mut start, "identifier", id);
         }
         Event::Empty(start)
     }
 }
 
 impl Anchor {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Event<'_> {
         let mut start = BytesStart::borrowed_name(b"anchor");
 
-        start.push_attribute(("x", self.x.to_string().as_str()));
-        start.push_attribute(("y", self.y.to_string().as_str()));
-
         if let Some(name) = &self.name {
-            start.push_attribute(("name", name.as_str()));
-        }
-
-        if let Some(Identifier(id)) = &self.identifier {
-            start.push_attribute(("identifier", id.as_str()));
+            push_attribute(&mut start, "name", name);
         }
-
+        push_attribute(&mut start, "x", &format_number(self.x));
+        push_attribute(&mut start, "y", &format_number(self.y));
         if let Some(color) = &self.color {
-            start.push_attribute(("color", color.to_rgba_string().as_str()));
+            push_attribute(&mut start, "color", &color.to_rgba_string());
+        }
+        if let Some(Identifier(id)) = &self.identifier {
+            push_attribute(&mut start, "identifier", id);
         }
         Event::Empty(start)
     }
 }
 
 impl Component {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Event<'_> {
         let mut start = BytesStart::borrowed_name(b"component");
-        start.push_attribute(("base", self.base.as_str()));
-        start.push_attribute(("xScale", self.transform.x_scale.to_string().as_str()));
-        start.push_attribute(("yScale", self.transform.y_scale.to_string().as_str()));
-        start.push_attribute(("xyScale", self.transform.xy_scale.to_string().as_str()));
-        start.push_attribute(("yxScale", self.transform.yx_scale.to_string().as_str()));
-        start.push_attribute(("xOffset", self.transform.x_offset.to_string().as_str()));
-        start.push_attribute(("yOffset", self.transform.y_offset.to_string().as_str()));
-
+        push_attribute(&mut start, "base", &self.base);
+        self.transform.push_attributes(&mut start);
         if let Some(Identifier(id)) = &self.identifier {
-            start.push_attribute(("identifier", id.as_str()));
+            push_attribute(&mut start, "identifier", id);
         }
         Event::Empty(start)
     }
@@ -163,7 +196,7 @@ impl Contour {
         let mut start = BytesStart::borrowed_name(b"contour");
 
         if let Some(Identifier(id)) = &self.identifier {
-            start.push_attribute(("identifier", id.as_str()));
+            push_attribute(&mut start, "identifier", id);
         }
 
         writer.write_event(Event::Start(start))?;
@@ -177,21 +210,21 @@ impl Contour {
 }
 
 impl ContourPoint {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Event<'_> {
         let mut start = BytesStart::borrowed_name(b"point");
-
-        start.push_attribute(("x", self.x.to_string().as_str()));
-        start.push_attribute(("y", self.y.to_string().as_str()));
-        let smooth = if self.smooth { "yes" } else { "no" };
-        start.push_attribute(("smooth", smooth));
-        start.push_attribute(("type", self.typ.as_str()));
-
+        push_attribute(&mut start, "x", &format_number(self.x));
+        push_attribute(&mut start, "y", &format_number(self.y));
+        if self.typ != PointType::OffCurve {
+            push_attribute(&mut start, "type", self.typ.as_str());
+            if self.smooth {
+                push_attribute(&mut start, "smooth", "yes");
+            }
+        }
         if let Some(name) = &self.name {
-            start.push_attribute(("name", name.as_str()));
+            push_attribute(&mut start, "name", name);
         }
-
         if let Some(Identifier(id)) = &self.identifier {
-            start.push_attribute(("identifier", id.as_str()));
+            push_attribute(&mut start, "identifier", id);
         }
         Event::Empty(start)
     }
@@ -210,29 +243,55 @@ impl PointType {
 }
 
 impl Color {
-    fn to_rgba_string(&self) -> String {
-        format!("{},{},{},{}", self.red, self.green, self.blue, self.alpha)
+    pub(crate) fn to_rgba_string(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            format_number(self.red),
+            format_number(self.green),
+            format_number(self.blue),
+            format_number(self.alpha)
+        )
+    }
+}
+
+impl AffineTransform {
+    /// Add the attributes for each value that differs from the identity
+    /// transformation.
+    fn push_attributes(&self, start: &mut BytesStart) {
+        let identity = AffineTransform::identity();
+        let values = [
+            ("xScale", self.x_scale, identity.x_scale),
+            ("xyScale", self.xy_scale, identity.xy_scale),
+            ("yxScale", self.yx_scale, identity.yx_scale),
+            ("yScale", self.y_scale, identity.y_scale),
+            ("xOffset", self.x_offset, identity.x_offset),
+            ("yOffset", self.y_offset, identity.y_offset),
+        ];
+        for (key, value, default) in values.iter() {
+            if value != default {
+                push_attribute(start, key, &format_number(*value));
+            }
+        }
     }
 }
 
 impl Image {
-    fn to_event(&self) -> Event {
+    fn to_event(&self) -> Event<'_> {
         let mut start = BytesStart::borrowed_name(b"image");
-        start.push_attribute(("fileName", self.file_name.to_str().unwrap_or("missing path")));
-        start.push_attribute(("xScale", self.transform.x_scale.to_string().as_str()));
-        start.push_attribute(("yScale", self.transform.y_scale.to_string().as_str()));
-        start.push_attribute(("xyScale", self.transform.xy_scale.to_string().as_str()));
-        start.push_attribute(("yxScale", self.transform.yx_scale.to_string().as_str()));
-        start.push_attribute(("xOffset", self.transform.x_offset.to_string().as_str()));
-        start.push_attribute(("yOffset", self.transform.y_offset.to_string().as_str()));
-
+        push_attribute(&mut start, "fileName", self.file_name.to_str().unwrap_or("missing path"));
+        self.transform.push_attributes(&mut start);
         if let Some(color) = &self.color {
-            start.push_attribute(("color", color.to_rgba_string().as_str()));
+            push_attribute(&mut start, "color", &color.to_rgba_string());
         }
         Event::Empty(start)
     }
 }
 
+/// Write `lib` as the `<dict>` inside a glyph's `<lib>` element.
+fn write_lib<T: Write>(lib: &Plist, writer: &mut Writer<T>) -> Result<(), XmlError> {
+    write::write_value(&plist::Value::Dictionary(lib.clone()), writer)
+}
+
 fn char_to_event(c: &char) -> Event<'static> {
     let mut start = BytesStart::borrowed_name(b"unicode");
     let hex = format!("{:04X}", *c as u32);
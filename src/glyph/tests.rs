@@ -23,13 +23,13 @@ fn parse() {
 fn curve_types() {
     let bytes = include_bytes!("../../testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs/D_.glif");
     let glyph = parse_glyph(bytes).unwrap();
-    let outline = glyph.outline.as_ref().unwrap();
-    assert_eq!(outline.contours.len(), 2);
-    assert_eq!(outline.contours[1].points[0].typ, PointType::Line);
-    assert_eq!(outline.contours[1].points[0].smooth, false);
-    assert_eq!(outline.contours[1].points[1].smooth, true);
-    assert_eq!(outline.contours[1].points[2].typ, PointType::OffCurve);
-    assert_eq!(outline.contours[1].points[4].typ, PointType::Curve);
+    let contours: Vec<_> = glyph.outline.as_ref().unwrap().contours().collect();
+    assert_eq!(contours.len(), 2);
+    assert_eq!(contours[1].points[0].typ, PointType::Line);
+    assert_eq!(contours[1].points[0].smooth, false);
+    assert_eq!(contours[1].points[1].smooth, true);
+    assert_eq!(contours[1].points[2].typ, PointType::OffCurve);
+    assert_eq!(contours[1].points[4].typ, PointType::Curve);
 }
 
 #[test]
@@ -37,7 +37,7 @@ fn guidelines() {
     let bytes = include_bytes!("../../testdata/Blinker_one.glif");
     let glyph = parse_glyph(bytes).unwrap();
     assert_eq!(glyph.guidelines.as_ref().map(Vec::len), Some(8));
-    assert_eq!(glyph.outline.as_ref().map(|o| o.contours.len()), Some(2));
+    assert_eq!(glyph.outline.as_ref().map(|o| o.contours().count()), Some(2));
     assert_eq!(glyph.advance, Some(Advance::Width(364.)));
 }
 
@@ -78,6 +78,204 @@ fn save() {
     assert_eq!(glyph.image, glyph2.image);
     assert_eq!(glyph.anchors, glyph2.anchors);
     assert_eq!(glyph.guidelines, glyph2.guidelines);
+    assert_eq!(glyph.lib, glyph2.lib);
+}
+
+#[test]
+fn convert_v1() {
+    let data = r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="A" format="1">
+  <advance width="600"/>
+  <outline>
+    <contour>
+      <point x="300" y="700" type="move" name="top"/>
+    </contour>
+    <contour>
+      <point x="100" y="0" type="move"/>
+    </contour>
+    <contour>
+      <point x="0" y="0" type="line"/>
+      <point x="300" y="700" type="line"/>
+      <point x="600" y="0" type="line"/>
+    </contour>
+  </outline>
+</glyph>
+"#;
+    let mut glyph = parse_glyph(data.as_bytes()).unwrap();
+    assert_eq!(glyph.format, GlifVersion::V1);
+    assert_eq!(glyph.outline.as_ref().unwrap().contours().count(), 3);
+    let raw = glyph.clone();
+
+    glyph.convert_to_v2();
+    assert_eq!(glyph.format, GlifVersion::V2);
+    assert_eq!(glyph.outline.as_ref().unwrap().contours().count(), 2);
+    let anchors = glyph.anchors.as_ref().unwrap();
+    assert_eq!(anchors.len(), 1);
+    assert_eq!(anchors[0].name.as_deref(), Some("top"));
+    assert_eq!((anchors[0].x, anchors[0].y), (300., 700.));
+
+    let mut converted = glyph.clone();
+    converted.convert_to_v2();
+    assert_eq!(converted, glyph);
+    assert_ne!(raw, glyph);
+}
+
+#[test]
+fn load_v1() {
+    let path = "testdata/ufo2test.ufo/glyphs/O_.glif";
+    let glyph = Glyph::load(path).unwrap();
+    assert_eq!(glyph.format, GlifVersion::V2);
+    assert_eq!(glyph.anchors.as_ref().map(Vec::len), Some(1));
+    assert_eq!(glyph.outline.as_ref().map(|o| o.contours().count()), Some(1));
+
+    let raw = Glyph::load_raw(path).unwrap();
+    assert_eq!(raw.format, GlifVersion::V1);
+    assert_eq!(raw.anchors, None);
+    assert_eq!(raw.outline.as_ref().map(|o| o.contours().count()), Some(2));
+}
+
+#[test]
+fn lib() {
+    let bytes = include_bytes!("../../testdata/sample_period.glif");
+    let glyph = parse_glyph(bytes).unwrap();
+    let lib = glyph.lib.as_ref().expect("missing lib");
+    assert_eq!(lib.len(), 3);
+    assert_eq!(
+        lib.get("com.letterror.somestuff").and_then(plist::Value::as_string),
+        Some("arbitrary custom data!")
+    );
+    let hints = lib.get("public.postscript.hints").and_then(plist::Value::as_dictionary).unwrap();
+    assert_eq!(hints.get("hintSetList").and_then(plist::Value::as_array).map(Vec::len), Some(2));
+    assert_eq!(glyph.mark_color(), Some(Color { red: 1., green: 0., blue: 0., alpha: 0.5 }));
+    assert_eq!(glyph.vertical_origin(), None);
+}
+
+#[test]
+fn empty_lib_element() {
+    let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="A" format="2">
+  <advance width="600"/>
+  <lib/>
+</glyph>
+"#;
+    let glyph = parse_glyph(data.as_bytes()).unwrap();
+    assert_eq!(glyph.lib, Some(Plist::new()));
+    assert_eq!(glyph.advance, Some(Advance::Width(600.)));
+}
+
+#[test]
+fn lib_accessors() {
+    let mut glyph = Glyph::new_named("A");
+    glyph.set_vertical_origin(Some(880.));
+    assert_eq!(glyph.lib.as_ref().unwrap().get("public.verticalOrigin"), Some(&880.into()));
+    assert_eq!(glyph.vertical_origin(), Some(880.));
+
+    let color = Color { red: 0., green: 0.5, blue: 1., alpha: 1. };
+    glyph.set_mark_color(Some(color.clone()));
+    assert_eq!(glyph.mark_color(), Some(color));
+
+    let mut object_libs = Plist::new();
+    let mut object_lib = Plist::new();
+    object_lib.insert("com.example.key".into(), true.into());
+    object_lib.insert("com.example.empty".into(), plist::Value::Array(Vec::new()));
+    object_lib.insert("com.example.data".into(), plist::Value::Data(vec![0, 1, 2, 255]));
+    object_libs.insert("id1".into(), plist::Value::Dictionary(object_lib.clone()));
+    glyph.lib.as_mut().unwrap().insert("public.objectLibs".into(), object_libs.into());
+    assert_eq!(glyph.object_lib("id1"), Some(&object_lib));
+
+    let buf = glyph.encode_xml().unwrap();
+    let glyph2 = parse_glyph(buf.as_slice()).unwrap();
+    assert_eq!(glyph.lib, glyph2.lib);
+
+    glyph.lib.as_mut().unwrap().remove("public.objectLibs");
+    glyph.set_mark_color(None);
+    glyph.set_vertical_origin(None);
+    assert_eq!(glyph.lib, None);
+}
+
+#[test]
+fn empty_lib() {
+    let mut glyph = Glyph::new_named("A");
+    glyph.lib = Some(Plist::new());
+    let buf = glyph.encode_xml().unwrap();
+    let glyph2 = parse_glyph(buf.as_slice()).unwrap();
+    assert_eq!(glyph2.lib, Some(Plist::new()));
+}
+
+#[test]
+fn outline_order() {
+    let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="Aacute" format="2">
+  <outline>
+    <contour>
+      <point x="0" y="0" type="line"/>
+      <point x="10" y="10" type="line"/>
+    </contour>
+    <component base="A"/>
+    <contour>
+      <point x="20" y="20" type="line"/>
+      <point x="30" y="30" type="line"/>
+    </contour>
+    <component base="acute"/>
+  </outline>
+</glyph>
+"#;
+    let mut glyph = parse_glyph(data.as_bytes()).unwrap();
+    let kinds = |glyph: &Glyph| -> String {
+        glyph
+            .outline
+            .as_ref()
+            .unwrap()
+            .elements
+            .iter()
+            .map(|element| match element {
+                OutlineElement::Contour(_) => 'o',
+                OutlineElement::Component(_) => 'c',
+            })
+            .collect()
+    };
+    assert_eq!(kinds(&glyph), "ococ");
+
+    let buf = glyph.encode_xml().unwrap();
+    let glyph2 = parse_glyph(buf.as_slice()).unwrap();
+    assert_eq!(kinds(&glyph2), "ococ");
+    assert_eq!(glyph, glyph2);
+
+    // removing the first component keeps the other elements in place
+    glyph.outline.as_mut().unwrap().elements.remove(1);
+    assert_eq!(kinds(&glyph), "ooc");
+    glyph.outline.as_mut().unwrap().push_contour(Contour { identifier: None, points: Vec::new() });
+    assert_eq!(kinds(&glyph), "ooco");
+    let bases: Vec<_> = glyph.outline.as_ref().unwrap().components().map(|c| &c.base).collect();
+    assert_eq!(bases, ["acute"]);
+    assert_ne!(glyph, glyph2);
+}
+
+#[test]
+fn format_minor() {
+    let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<glyph name="A" format="2" formatMinor="2">
+  <advance width="600"/>
+</glyph>
+"#;
+    let glyph = parse_glyph(data.as_bytes()).unwrap();
+    assert_eq!(glyph.format, GlifVersion::V2);
+    assert_eq!(glyph.format_minor, 2);
+    assert!(glyph.has_newer_format_minor());
+    assert!(!Glyph::new_named("A").has_newer_format_minor());
+
+    let buf = glyph.encode_xml().unwrap();
+    assert!(String::from_utf8_lossy(&buf).contains(r#"formatMinor="2""#));
+    assert_eq!(parse_glyph(buf.as_slice()).unwrap(), glyph);
+
+    let buf = Glyph::new_named("A").encode_xml().unwrap();
+    assert!(!String::from_utf8_lossy(&buf).contains("formatMinor"));
+
+    let data = r#"<glyph name="A" format="3"></glyph>"#;
+    assert!(parse_glyph(data.as_bytes()).is_err());
+    let data = r#"<glyph name="A" format="2" formatMinor="x"></glyph>"#;
+    assert!(parse_glyph(data.as_bytes()).is_err());
 }
 
 //#[test]
@@ -86,3 +284,28 @@ fn save() {
 //let glyph = parse_glyph(bytes).unwrap();
 //assert_eq!(glyph.width, Some(268.));
 //}
+
+#[test]
+fn normalize_glif() {
+    // the same glyph as the normalized test font's A_acute.glif, with the
+    // attributes, numbers and whitespace written differently
+    let data = r#"<?xml version='1.0' encoding='UTF-8'?>
+<glyph format="2" name="Aacute">
+  <unicode hex="00c1"/>
+  <advance width="600.0"/>
+  <note>Built from A &lt; acutecomb.</note>
+  <outline>
+    <component base="A"/>
+    <contour>
+      <point type="move" y="750.30" x="250.1"/>
+      <point y="850.0625" x="349.990" type="line"/>
+    </contour>
+    <component yScale="0.3333333" xScale="0.75" base="acutecomb" yOffset="-10.50" xOffset="300.0" identifier="component1"/>
+  </outline>
+</glyph>
+"#;
+    let glyph = parse_glyph(data.as_bytes()).unwrap();
+    let buf = glyph.encode_xml().unwrap();
+    let expected = include_str!("../../testdata/normalizedtest.ufo/glyphs/A_acute.glif");
+    assert_eq!(String::from_utf8(buf).unwrap(), expected);
+}
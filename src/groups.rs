@@ -0,0 +1,246 @@
+//! The contents of the [`groups.plist`][groups] file.
+//!
+//! [groups]: http://unifiedfontobject.org/versions/ufo3/groups.plist/
+
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+
+use serde_derive::{Deserialize, Serialize};
+
+/// The prefix of groups used on the first side of a kerning pair.
+pub static KERN1_PREFIX: &str = "public.kern1.";
+/// The prefix of groups used on the second side of a kerning pair.
+pub static KERN2_PREFIX: &str = "public.kern2.";
+
+/// A map of group names to ordered lists of glyph names.
+///
+/// Groups whose names start with `public.kern1.` or `public.kern2.` are
+/// kerning groups; a glyph may belong to at most one kerning group on
+/// each side.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Groups(BTreeMap<String, Vec<String>>);
+
+/// The side of a kerning pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KerningSide {
+    /// The first (left, in left-to-right text) glyph of a pair.
+    First,
+    /// The second glyph of a pair.
+    Second,
+}
+
+/// A glyph that belongs to more than one kerning group on the same side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KerningGroupConflict {
+    pub glyph: String,
+    pub side: KerningSide,
+    /// The names of all the groups on `side` that contain `glyph`.
+    pub groups: Vec<String>,
+}
+
+/// The reason a set of groups is invalid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupsValidationError {
+    /// A kerning group has nothing after its prefix.
+    InvalidName(String),
+    /// One or more glyphs belong to several kerning groups on the same side.
+    OverlappingKerningGroups(Vec<KerningGroupConflict>),
+}
+
+impl Groups {
+    /// Create a new, empty set of groups.
+    pub fn new() -> Self {
+        Groups::default()
+    }
+
+    /// Returns the glyphs in the named group.
+    pub fn get(&self, name: &str) -> Option<&Vec<String>> {
+        self.0.get(name)
+    }
+
+    /// Returns a mutable reference to the glyphs in the named group.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Vec<String>> {
+        self.0.get_mut(name)
+    }
+
+    /// Set the glyphs of the named group, returning the previous glyphs
+    /// if the group already existed.
+    pub fn insert<S: Into<String>>(&mut self, name: S, glyphs: Vec<String>) -> Option<Vec<String>> {
+        self.0.insert(name.into(), glyphs)
+    }
+
+    /// Remove the named group, returning its glyphs.
+    pub fn remove(&mut self, name: &str) -> Option<Vec<String>> {
+        self.0.remove(name)
+    }
+
+    /// Returns `true` if a group with this name exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// The number of groups.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no groups.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over all groups and their glyphs, sorted by name.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Vec<String>> {
+        self.0.iter()
+    }
+
+    /// Returns `true` if `glyph` belongs to any group.
+    pub(crate) fn contains_glyph(&self, glyph: &str) -> bool {
+        self.0.values().flatten().any(|g| g == glyph)
+    }
+
+    /// Replace the glyph name `old` with `new` in every group.
+    pub(crate) fn rename_glyph(&mut self, old: &str, new: &str) {
+        for glyph in self.0.values_mut().flatten().filter(|g| *g == old) {
+            *glyph = new.to_string();
+        }
+    }
+
+    /// Returns an iterator over the kerning groups for one side of a pair.
+    pub fn kerning_groups(
+        &self,
+        side: KerningSide,
+    ) -> impl Iterator<Item = (&String, &Vec<String>)> {
+        self.0.iter().filter(move |(name, _)| kerning_side(name) == Some(side))
+    }
+
+    /// Returns the name of the kerning group on `side` that contains `glyph`.
+    ///
+    /// If the groups are invalid and the glyph belongs to several groups,
+    /// the first group (by name) is returned.
+    pub fn kerning_group_for_glyph(&self, glyph: &str, side: KerningSide) -> Option<&str> {
+        self.kerning_groups(side)
+            .find(|(_, glyphs)| glyphs.iter().any(|g| g == glyph))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns every glyph that belongs to more than one kerning group on
+    /// the same side.
+    pub fn kerning_conflicts(&self) -> Vec<KerningGroupConflict> {
+        let mut memberships: BTreeMap<(&str, KerningSide), BTreeSet<&str>> = BTreeMap::new();
+        for (name, glyphs) in self.0.iter() {
+            if let Some(side) = kerning_side(name) {
+                for glyph in glyphs {
+                    memberships.entry((glyph, side)).or_default().insert(name);
+                }
+            }
+        }
+
+        memberships
+            .into_iter()
+            .filter(|(_, groups)| groups.len() > 1)
+            .map(|((glyph, side), groups)| KerningGroupConflict {
+                glyph: glyph.to_string(),
+                side,
+                groups: groups.into_iter().map(String::from).collect(),
+            })
+            .collect()
+    }
+
+    /// Check that these groups follow the rules for kerning groups.
+    pub fn validate(&self) -> Result<(), GroupsValidationError> {
+        if let Some(name) = self
+            .0
+            .keys()
+            .find(|name| name.as_str() == KERN1_PREFIX || name.as_str() == KERN2_PREFIX)
+        {
+            return Err(GroupsValidationError::InvalidName(name.clone()));
+        }
+
+        let conflicts = self.kerning_conflicts();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(GroupsValidationError::OverlappingKerningGroups(conflicts))
+        }
+    }
+}
+
+/// Returns the kerning side of the group with this name, if it is a kerning group.
+pub fn kerning_side(group_name: &str) -> Option<KerningSide> {
+    if group_name.starts_with(KERN1_PREFIX) {
+        Some(KerningSide::First)
+    } else if group_name.starts_with(KERN2_PREFIX) {
+        Some(KerningSide::Second)
+    } else {
+        None
+    }
+}
+
+impl<'a> IntoIterator for &'a Groups {
+    type Item = (&'a String, &'a Vec<String>);
+    type IntoIter = btree_map::Iter<'a, String, Vec<String>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for Groups {
+    fn from(src: BTreeMap<String, Vec<String>>) -> Groups {
+        Groups(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyphs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn load_groups() {
+        let groups: Groups = plist::from_file("testdata/kerningtest.ufo/groups.plist").unwrap();
+        assert_eq!(groups.get("public.kern1.O"), Some(&glyphs(&["O", "D", "Q"])));
+        assert_eq!(groups.kerning_groups(KerningSide::First).count(), 2);
+        assert_eq!(groups.kerning_groups(KerningSide::Second).count(), 2);
+        assert_eq!(groups.kerning_group_for_glyph("Q", KerningSide::First), Some("public.kern1.O"));
+        assert_eq!(groups.kerning_group_for_glyph("Q", KerningSide::Second), None);
+        assert!(groups.validate().is_ok());
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut groups = Groups::new();
+        groups.insert("public.kern1.O", glyphs(&["O", "D"]));
+        groups.insert("public.kern1.D", glyphs(&["D"]));
+        groups.insert("public.kern2.D", glyphs(&["D"]));
+        groups.insert("round", glyphs(&["O", "D"]));
+
+        let conflicts = groups.kerning_conflicts();
+        assert_eq!(
+            conflicts,
+            vec![KerningGroupConflict {
+                glyph: "D".into(),
+                side: KerningSide::First,
+                groups: glyphs(&["public.kern1.D", "public.kern1.O"]),
+            }]
+        );
+        assert_eq!(
+            groups.validate(),
+            Err(GroupsValidationError::OverlappingKerningGroups(conflicts))
+        );
+    }
+
+    #[test]
+    fn invalid_name() {
+        let mut groups = Groups::new();
+        groups.insert("public.kern2.", glyphs(&["O"]));
+        assert_eq!(
+            groups.validate(),
+            Err(GroupsValidationError::InvalidName("public.kern2.".into()))
+        );
+    }
+}
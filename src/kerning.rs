@@ -0,0 +1,177 @@
+//! The contents of the [`kerning.plist`][kerning] file.
+//!
+//! [kerning]: http://unifiedfontobject.org/versions/ufo3/kerning.plist/
+
+use std::collections::BTreeMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::fontinfo::IntegerOrFloat;
+use crate::groups::{Groups, KerningSide};
+
+/// Kerning pairs, keyed by the first and then the second member of the pair.
+///
+/// Each member of a pair is either a glyph name or the name of a kerning
+/// group in [`Groups`].
+///
+/// [`Groups`]: ../groups/struct.Groups.html
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Kerning(BTreeMap<String, BTreeMap<String, IntegerOrFloat>>);
+
+impl Kerning {
+    /// Create a new, empty set of kerning pairs.
+    pub fn new() -> Self {
+        Kerning::default()
+    }
+
+    /// Returns the value of the pair with exactly these members, without
+    /// resolving groups.
+    pub fn get(&self, first: &str, second: &str) -> Option<f64> {
+        self.0.get(first).and_then(|seconds| seconds.get(second)).map(|v| v.get())
+    }
+
+    /// Set the value of a pair, returning the previous value if the pair existed.
+    pub fn insert<S1, S2>(&mut self, first: S1, second: S2, value: f64) -> Option<f64>
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        let seconds = self.0.entry(first.into()).or_default();
+        seconds.insert(second.into(), value.into()).map(|v| v.get())
+    }
+
+    /// Remove a pair, returning its value if it existed.
+    pub fn remove(&mut self, first: &str, second: &str) -> Option<f64> {
+        let seconds = self.0.get_mut(first)?;
+        let value = seconds.remove(second);
+        if seconds.is_empty() {
+            self.0.remove(first);
+        }
+        value.map(|v| v.get())
+    }
+
+    /// The number of pairs.
+    pub fn len(&self) -> usize {
+        self.0.values().map(BTreeMap::len).sum()
+    }
+
+    /// Returns `true` if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(BTreeMap::is_empty)
+    }
+
+    /// Returns an iterator over all pairs as `(first, second, value)`, sorted
+    /// by `first` and then `second`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, f64)> {
+        self.0.iter().flat_map(|(first, seconds)| {
+            seconds
+                .iter()
+                .map(move |(second, value)| (first.as_str(), second.as_str(), value.get()))
+        })
+    }
+
+    /// Returns `true` if `glyph` is the first or second member of any pair.
+    pub(crate) fn contains_glyph(&self, glyph: &str) -> bool {
+        self.0.iter().any(|(first, seconds)| first == glyph || seconds.contains_key(glyph))
+    }
+
+    /// Replace the glyph name `old` with `new` on both sides of every pair.
+    pub(crate) fn rename_glyph(&mut self, old: &str, new: &str) {
+        if let Some(seconds) = self.0.remove(old) {
+            self.0.entry(new.to_string()).or_default().extend(seconds);
+        }
+        for seconds in self.0.values_mut() {
+            if let Some(value) = seconds.remove(old) {
+                seconds.insert(new.to_string(), value);
+            }
+        }
+    }
+
+    /// Returns the kerning value for a pair of glyphs, resolving kerning groups.
+    ///
+    /// Following the [spec], the first of these pairs that exists is used:
+    ///
+    /// 1. the first glyph and the second glyph
+    /// 2. the first glyph and the second glyph's `public.kern2.` group
+    /// 3. the first glyph's `public.kern1.` group and the second glyph
+    /// 4. the first glyph's group and the second glyph's group
+    ///
+    /// Returns `None` if no pair applies.
+    ///
+    /// [spec]: http://unifiedfontobject.org/versions/ufo3/kerning.plist/#kerning-value-lookup-algorithm
+    pub fn lookup(&self, first: &str, second: &str, groups: &Groups) -> Option<f64> {
+        let first_group = groups.kerning_group_for_glyph(first, KerningSide::First);
+        let second_group = groups.kerning_group_for_glyph(second, KerningSide::Second);
+
+        self.get(first, second)
+            .or_else(|| second_group.and_then(|second| self.get(first, second)))
+            .or_else(|| first_group.and_then(|first| self.get(first, second)))
+            .or_else(|| match (first_group, second_group) {
+                (Some(first), Some(second)) => self.get(first, second),
+                _ => None,
+            })
+    }
+}
+
+impl From<BTreeMap<String, BTreeMap<String, f64>>> for Kerning {
+    fn from(src: BTreeMap<String, BTreeMap<String, f64>>) -> Kerning {
+        let inner = src
+            .into_iter()
+            .map(|(first, seconds)| {
+                (first, seconds.into_iter().map(|(second, v)| (second, v.into())).collect())
+            })
+            .collect();
+        Kerning(inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_test_data() -> (Kerning, Groups) {
+        let kerning = plist::from_file("testdata/kerningtest.ufo/kerning.plist").unwrap();
+        let groups = plist::from_file("testdata/kerningtest.ufo/groups.plist").unwrap();
+        (kerning, groups)
+    }
+
+    #[test]
+    fn load_kerning() {
+        let (kerning, _) = load_test_data();
+        assert_eq!(kerning.len(), 7);
+        assert_eq!(kerning.get("T", "O"), Some(-30.));
+        assert_eq!(kerning.get("V", "A"), Some(-12.5));
+        assert_eq!(kerning.get("T", "A"), None);
+        assert_eq!(kerning.iter().next(), Some(("T", "O", -30.)));
+    }
+
+    #[test]
+    fn lookup() {
+        let (kerning, groups) = load_test_data();
+        // glyph, glyph
+        assert_eq!(kerning.lookup("T", "O", &groups), Some(-30.));
+        assert_eq!(kerning.lookup("V", "A", &groups), Some(-12.5));
+        // glyph, group
+        assert_eq!(kerning.lookup("V", "Aacute", &groups), Some(-40.));
+        // group, glyph
+        assert_eq!(kerning.lookup("D", "A", &groups), Some(-15.));
+        // group, group
+        assert_eq!(kerning.lookup("D", "Aacute", &groups), Some(-20.));
+        assert_eq!(kerning.lookup("T", "C", &groups), Some(-50.));
+        assert_eq!(kerning.lookup("T", "Aacute", &groups), Some(-60.));
+        // no pair
+        assert_eq!(kerning.lookup("A", "V", &groups), None);
+        assert_eq!(kerning.lookup("V", "O", &groups), None);
+    }
+
+    #[test]
+    fn edit_pairs() {
+        let mut kerning = Kerning::new();
+        assert_eq!(kerning.insert("A", "V", -10.), None);
+        assert_eq!(kerning.insert("A", "V", -20.), Some(-10.));
+        assert_eq!(kerning.len(), 1);
+        assert_eq!(kerning.remove("A", "V"), Some(-20.));
+        assert!(kerning.is_empty());
+    }
+}
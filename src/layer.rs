@@ -1,11 +1,22 @@
-use std::collections::BTreeMap;
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::{Error, Glyph};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::downconversion::{self, DataLoss};
+use crate::glyph::serde_impls::optional_lib;
+use crate::glyph::{Color, GlifVersion, Guideline, Plist};
+use crate::names::user_name_to_file_name;
+use crate::write;
+use crate::{Error, FormatVersion, Glyph};
 
 static CONTENTS_FILE: &str = "contents.plist";
-//static LAYER_INFO_FILE: &str = "layerinfo.plist";
+static LAYER_INFO_FILE: &str = "layerinfo.plist";
 
 /// A [layer], corresponding to a 'glyphs' directory. Conceptually, a layer
 /// is just a collection of glyphs.
@@ -14,35 +25,107 @@ static CONTENTS_FILE: &str = "contents.plist";
 pub struct Layer {
     path: PathBuf,
     contents: BTreeMap<String, PathBuf>,
+    /// The lowercased file names in `contents`, for finding unused names.
+    file_names: HashSet<String>,
     loaded: BTreeMap<String, Entry>,
+    /// The color used to show this layer in an editor, from `layerinfo.plist`.
+    pub color: Option<Color>,
+    /// Guidelines that apply to every glyph in this layer, from `layerinfo.plist`.
+    pub guidelines: Option<Vec<Guideline>>,
+    /// Arbitrary data about this layer, from `layerinfo.plist`.
+    pub lib: Option<Plist>,
+    /// Keep GLIF format 1 glyphs as they were written when loading them.
+    pub(crate) keep_raw_glyphs: bool,
+    /// The format of the font this layer was loaded from. Glyphs in fonts
+    /// older than UFO3 may be in GLIF format 1.
+    pub(crate) source_format: FormatVersion,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Layer::new()
+    }
+}
+
+/// The contents of `layerinfo.plist`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LayerInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<Color>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    guidelines: Option<Vec<Guideline>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "optional_lib")]
+    lib: Option<Plist>,
 }
 
 enum Entry {
-    Loaded(Glyph),
-    // Boxed so we can clone
-    Errored(Rc<Error>),
+    Loaded(Box<Glyph>),
+    // shared, so the error can be returned each time the glyph is requested
+    Errored(Arc<Error>),
 }
 
 impl Layer {
     pub fn load<P: Into<PathBuf>>(path: P) -> Result<Layer, Error> {
         let path = path.into();
         let contents_path = path.join(CONTENTS_FILE);
-        let contents = plist::from_file(contents_path)?;
-        Ok(Layer { path, contents, loaded: BTreeMap::new() })
+        let contents: BTreeMap<String, PathBuf> = plist::from_file(contents_path)?;
+        let file_names = contents.values().map(|p| lowercase_path(p)).collect();
+        let mut layer = Layer {
+            path,
+            contents,
+            file_names,
+            loaded: BTreeMap::new(),
+            color: None,
+            guidelines: None,
+            lib: None,
+            keep_raw_glyphs: false,
+            source_format: FormatVersion::V3,
+        };
+
+        let info_path = layer.path.join(LAYER_INFO_FILE);
+        if info_path.exists() {
+            let info: LayerInfo = plist::from_file(info_path)?;
+            layer.color = info.color;
+            layer.guidelines = info.guidelines;
+            layer.lib = info.lib;
+        }
+        Ok(layer)
+    }
+
+    /// Create a new, empty layer.
+    ///
+    /// The layer is not backed by a directory until it is saved.
+    pub fn new() -> Layer {
+        Layer {
+            path: PathBuf::new(),
+            contents: BTreeMap::new(),
+            file_names: HashSet::new(),
+            loaded: BTreeMap::new(),
+            color: None,
+            guidelines: None,
+            lib: None,
+            keep_raw_glyphs: false,
+            source_format: FormatVersion::V3,
+        }
     }
 
     /// Attempt to load and return the glyph with this name.
     ///
     /// Glyphs are lazily loaded from files on disk, so this function may
-    /// fail if a glyph file cannot be read.
+    /// fail if a glyph file cannot be read. Returns [`Error::MissingGlyph`]
+    /// if the layer has no glyph with this name.
+    ///
+    /// [`Error::MissingGlyph`]: enum.Error.html#variant.MissingGlyph
     pub fn get_glyph(&mut self, glyph: &str) -> Result<&Glyph, Error> {
         if !self.loaded.contains_key(glyph) {
-            self.load_glyph(glyph);
+            let path = self.contents.get(glyph).ok_or(Error::MissingGlyph)?;
+            let path = self.path.join(path);
+            self.load_glyph(glyph, &path);
         }
 
         match self.loaded.get(glyph).expect("glyph always loaded before get") {
-            Entry::Loaded(ref g) => return Ok(g),
-            Entry::Errored(e) => return Err(Error::SavedError(e.clone())),
+            Entry::Loaded(ref g) => Ok(g),
+            Entry::Errored(e) => Err(Error::SavedError(e.clone())),
         }
     }
 
@@ -51,40 +134,340 @@ impl Layer {
         self.loaded.contains_key(name) | self.contents.contains_key(name)
     }
 
+    /// Returns an iterator over the names of the glyphs in this layer,
+    /// in sorted order.
+    pub fn glyph_names(&self) -> impl Iterator<Item = &str> {
+        self.contents.keys().map(String::as_str)
+    }
+
     /// Set the given glyph. The name is taken from the glyph's `name` field.
     /// This replaces any existing glyph with this name.
-    pub fn set_glyph<P: Into<PathBuf>>(&mut self, path: P, glyph: Glyph) {
-        //FIXME: figure out what bookkeeping we have to do with this path
-        let _path = path.into();
+    ///
+    /// If this is a new glyph, its file name is derived from its name using
+    /// [`user_name_to_file_name`].
+    ///
+    /// [`user_name_to_file_name`]: fn.user_name_to_file_name.html
+    pub fn set_glyph(&mut self, glyph: Glyph) {
         let name = glyph.name.clone();
-        self.loaded.insert(name.clone(), Entry::Loaded(glyph));
+        if !self.contents.contains_key(&name) {
+            self.insert_new_file_name(&name);
+        }
+        self.loaded.insert(name, Entry::Loaded(Box::new(glyph)));
     }
 
     /// Remove the named glyph from this layer.
+    ///
+    /// The glyph's file will be removed when the layer is saved.
     pub fn delete_glyph(&mut self, name: &str) {
         self.loaded.remove(name);
-        self.contents.remove(name);
+        self.remove_file_name(name);
+    }
+
+    /// Write this layer to the directory at `path`.
+    ///
+    /// This writes `contents.plist`, `layerinfo.plist` if the layer has a
+    /// color, guidelines or lib, and a `.glif` file for each glyph in the
+    /// layer. Glyphs that have been loaded are serialized; glyphs that have
+    /// not been touched are copied verbatim from the source directory, or
+    /// left in place if `path` is the directory the layer was loaded from.
+    ///
+    /// Any `.glif` files in the directory that do not belong to a glyph in
+    /// this layer, such as the files of deleted glyphs, are removed.
+    ///
+    /// If a glyph failed to load, its error is returned before anything is
+    /// written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.save_impl(path.as_ref(), FormatVersion::V3, &mut Vec::new())
     }
 
-    fn load_glyph(&mut self, glyph: &str) {
-        let glif = match self.load_glyph_impl(&glyph) {
-            Ok(g) => Entry::Loaded(g),
-            Err(e) => Entry::Errored(Rc::new(e)),
+    /// Write this layer as part of a font in the given format.
+    ///
+    /// For formats older than UFO3, `layerinfo.plist` is not written and all
+    /// glyphs are written as GLIF format 1; anything that can't be expressed
+    /// is added to `losses`.
+    pub(crate) fn save_impl(
+        &self,
+        path: &Path,
+        format: FormatVersion,
+        losses: &mut Vec<DataLoss>,
+    ) -> Result<(), Error> {
+        let glyphs = self.glyphs_to_write(format, losses)?;
+        self.write_glyphs(path, format, &glyphs, losses)
+    }
+
+    /// Returns the glyphs that have to be serialized to save this layer in
+    /// the given format, rather than copied, converted to that format.
+    ///
+    /// Glyphs that haven't been loaded are read here if they need converting,
+    /// so that every error is returned before anything is written.
+    pub(crate) fn glyphs_to_write(
+        &self,
+        format: FormatVersion,
+        losses: &mut Vec<DataLoss>,
+    ) -> Result<BTreeMap<&str, Cow<'_, Glyph>>, Error> {
+        self.check_glyph_errors()?;
+        let mut glyphs = BTreeMap::new();
+        for (name, glyph_path) in self.contents.iter() {
+            let loaded = match self.loaded.get(name) {
+                Some(Entry::Loaded(glyph)) => Some(glyph.as_ref()),
+                Some(Entry::Errored(_)) => unreachable!("errored glyphs are checked above"),
+                None => None,
+            };
+
+            let glyph = if format < FormatVersion::V3 {
+                let glyph = match loaded {
+                    Some(glyph) => downconversion::glyph_to_v1(glyph, losses),
+                    None => downconversion::glyph_to_v1(
+                        &Glyph::load(self.path.join(glyph_path))?,
+                        losses,
+                    ),
+                };
+                Cow::Owned(glyph)
+            } else {
+                match loaded {
+                    // glyphs kept in GLIF format 1 are converted for UFO3
+                    Some(glyph) if glyph.format == GlifVersion::V1 || glyph.is_converted() => {
+                        let mut glyph = glyph.clone();
+                        glyph.convert_to_v2();
+                        glyph.remove_conversion_marker();
+                        Cow::Owned(glyph)
+                    }
+                    Some(glyph) => Cow::Borrowed(glyph),
+                    None if self.source_format < FormatVersion::V3 => {
+                        let mut glyph = Glyph::load(self.path.join(glyph_path))?;
+                        glyph.remove_conversion_marker();
+                        Cow::Owned(glyph)
+                    }
+                    None => continue,
+                }
+            };
+            glyphs.insert(name.as_str(), glyph);
+        }
+        Ok(glyphs)
+    }
+
+    /// Write the layer to `path`, serializing `glyphs` and copying every
+    /// other glyph from the source directory.
+    pub(crate) fn write_glyphs(
+        &self,
+        path: &Path,
+        format: FormatVersion,
+        glyphs: &BTreeMap<&str, Cow<'_, Glyph>>,
+        losses: &mut Vec<DataLoss>,
+    ) -> Result<(), Error> {
+        fs::create_dir_all(path)?;
+        let same_dir = is_same_dir(&self.path, path);
+        self.remove_stale_files(path)?;
+        write::write_plist(&path.join(CONTENTS_FILE), &self.contents)?;
+
+        let info_path = path.join(LAYER_INFO_FILE);
+        let info = LayerInfo {
+            color: self.color.clone(),
+            guidelines: self.guidelines.clone(),
+            lib: self.lib.clone(),
+        };
+        let has_info = info.color.is_some() || info.guidelines.is_some() || info.lib.is_some();
+        if has_info && format < FormatVersion::V3 {
+            losses.push(DataLoss::LayerInfo);
+        }
+        if has_info && format == FormatVersion::V3 {
+            write::write_plist(&info_path, &info)?;
+        } else if info_path.exists() {
+            fs::remove_file(info_path)?;
+        }
+
+        for (name, glyph_path) in self.contents.iter() {
+            match glyphs.get(name.as_str()) {
+                Some(glyph) => glyph.save(path.join(glyph_path))?,
+                None if same_dir => (),
+                None => {
+                    fs::copy(self.path.join(glyph_path), path.join(glyph_path))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the error of the first glyph that failed to load, if any.
+    fn check_glyph_errors(&self) -> Result<(), Error> {
+        match self.loaded.values().find_map(|entry| match entry {
+            Entry::Errored(e) => Some(e),
+            Entry::Loaded(_) => None,
+        }) {
+            Some(error) => Err(Error::SavedError(error.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Give every glyph the file name derived from its glyph name.
+    ///
+    /// Glyphs that have not been loaded are left alone, since they still
+    /// have to be read from their current file.
+    pub(crate) fn normalize_file_names(&mut self) {
+        // the paths of unloaded glyphs are final, so reserve them up front
+        let mut existing: HashSet<String> = self
+            .contents
+            .iter()
+            .filter(|(name, _)| !self.loaded.contains_key(*name))
+            .map(|(_, path)| lowercase_path(path))
+            .collect();
+        let mut contents = BTreeMap::new();
+        for (name, path) in self.contents.iter() {
+            let path = if self.loaded.contains_key(name) {
+                let path = user_name_to_file_name(name, "", ".glif", |c| existing.contains(c));
+                existing.insert(path.to_lowercase());
+                path.into()
+            } else {
+                path.clone()
+            };
+            contents.insert(name.clone(), path);
+        }
+        self.contents = contents;
+        self.file_names = existing;
+    }
+
+    /// Give the loaded glyph `old` the name `new`, along with a file name
+    /// derived from the new name.
+    pub(crate) fn rename_glyph(&mut self, old: &str, new: &str) {
+        let mut entry = match self.loaded.remove(old) {
+            Some(entry) => entry,
+            None => return,
+        };
+        self.remove_file_name(old);
+        if let Entry::Loaded(glyph) = &mut entry {
+            glyph.name = new.to_string();
+        }
+        self.insert_new_file_name(new);
+        self.loaded.insert(new.to_string(), entry);
+    }
+
+    /// Make components in loaded glyphs that use `old` as their base use
+    /// `new` instead.
+    pub(crate) fn rename_component_bases(&mut self, old: &str, new: &str) {
+        for entry in self.loaded.values_mut() {
+            if let Entry::Loaded(glyph) = entry {
+                let components = glyph.outline.iter_mut().flat_map(|o| o.components_mut());
+                for component in components.filter(|c| c.base == old) {
+                    component.base = new.to_string();
+                }
+            }
+        }
+    }
+
+    /// Add `name` to the contents, with a file name derived from it that
+    /// isn't used by any other glyph.
+    fn insert_new_file_name(&mut self, name: &str) {
+        let file_names = &self.file_names;
+        let path = user_name_to_file_name(name, "", ".glif", |c| file_names.contains(c));
+        self.file_names.insert(path.to_lowercase());
+        self.contents.insert(name.to_string(), path.into());
+    }
+
+    fn remove_file_name(&mut self, name: &str) {
+        if let Some(path) = self.contents.remove(name) {
+            self.file_names.remove(&lowercase_path(&path));
+        }
+    }
+
+    /// Remove `.glif` files in `path` that are not listed in our contents.
+    fn remove_stale_files(&self, path: &Path) -> Result<(), Error> {
+        let in_use: HashSet<&Path> = self.contents.values().map(PathBuf::as_path).collect();
+        for entry in fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            let is_glif = entry_path.extension().map(|ext| ext == "glif").unwrap_or(false);
+            let file_name = entry_path.file_name().map(Path::new);
+            if is_glif && !file_name.map(|n| in_use.contains(n)).unwrap_or(false) {
+                fs::remove_file(&entry_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load every glyph that has not been loaded yet, or that failed to load
+    /// before, returning the name and error of each glyph that failed.
+    ///
+    /// With the `rayon` feature, glyphs are parsed in parallel.
+    pub(crate) fn load_all_glyphs(&mut self) -> Vec<(String, Error)> {
+        let to_load: Vec<(&String, PathBuf)> = self
+            .contents
+            .iter()
+            .filter(|(name, _)| !matches!(self.loaded.get(*name), Some(Entry::Loaded(_))))
+            .map(|(name, path)| (name, self.path.join(path)))
+            .collect();
+
+        #[cfg(feature = "rayon")]
+        let iter = to_load.into_par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let iter = to_load.into_iter();
+        let keep_raw = self.keep_raw_glyphs;
+        let results: Vec<(String, Result<Glyph, Error>)> =
+            iter.map(|(name, path)| (name.clone(), load_glyph_file(&path, keep_raw))).collect();
+
+        let mut errors = Vec::new();
+        for (name, result) in results {
+            match result {
+                Ok(glyph) => {
+                    self.loaded.insert(name, Entry::Loaded(Box::new(glyph)));
+                }
+                Err(e) => errors.push((name, e)),
+            }
+        }
+        errors
+    }
+
+    /// Returns the name of each glyph that has an image, along with the
+    /// image's file name.
+    ///
+    /// Glyphs that have not been loaded are read from disk, but are not kept.
+    pub(crate) fn image_references(&self) -> Result<Vec<(&str, PathBuf)>, Error> {
+        let mut references = Vec::new();
+        for (name, glyph_path) in self.contents.iter() {
+            let image = match self.loaded.get(name) {
+                Some(Entry::Loaded(glyph)) => glyph.image.as_ref().map(|i| i.file_name.clone()),
+                Some(Entry::Errored(e)) => return Err(Error::SavedError(e.clone())),
+                None => Glyph::load(self.path.join(glyph_path))?.image.map(|i| i.file_name),
+            };
+            if let Some(image) = image {
+                references.push((name.as_str(), image));
+            }
+        }
+        Ok(references)
+    }
+
+    fn load_glyph(&mut self, glyph: &str, path: &Path) {
+        let glif = match load_glyph_file(path, self.keep_raw_glyphs) {
+            Ok(g) => Entry::Loaded(Box::new(g)),
+            Err(e) => Entry::Errored(Arc::new(e)),
         };
         self.loaded.insert(glyph.to_owned(), glif);
     }
+}
+
+fn load_glyph_file(path: &Path, keep_raw: bool) -> Result<Glyph, Error> {
+    if keep_raw {
+        Glyph::load_raw(path)
+    } else {
+        Glyph::load(path)
+    }
+}
+
+fn lowercase_path(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
 
-    fn load_glyph_impl(&mut self, glyph: &str) -> Result<Glyph, Error> {
-        let path = self.contents.get(glyph).ok_or(Error::MissingGlyph)?;
-        let path = self.path.join(path);
-        Glyph::load(&path)
+/// Returns `true` if both paths exist and resolve to the same directory.
+pub(crate) fn is_same_dir(one: &Path, two: &Path) -> bool {
+    match (fs::canonicalize(one), fs::canonicalize(two)) {
+        (Ok(one), Ok(two)) => one == two,
+        _ => false,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::glyph::Advance;
+    use crate::glyph::{Advance, Line};
+    use plist::Value;
     use std::path::Path;
 
     #[test]
@@ -98,6 +481,29 @@ mod tests {
         assert_eq!(glyph.codepoints.as_ref().unwrap()[0], 'A');
     }
 
+    #[test]
+    fn new_layer() {
+        let dir = tempdir::TempDir::new("layer").unwrap();
+        let mut layer = Layer::new();
+        assert_eq!(layer.glyph_names().count(), 0);
+        layer.set_glyph(Glyph::new_named("A"));
+        layer.save(dir.path()).unwrap();
+
+        let mut loaded = Layer::load(dir.path()).unwrap();
+        assert_eq!(loaded.glyph_names().collect::<Vec<_>>(), vec!["A"]);
+        assert!(loaded.get_glyph("A").is_ok());
+        assert!(!dir.path().join("layerinfo.plist").exists());
+    }
+
+    #[test]
+    fn get_missing_glyph() {
+        let dir = tempdir::TempDir::new("layer").unwrap();
+        let mut layer = Layer::load("testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs").unwrap();
+        assert!(matches!(layer.get_glyph("nope"), Err(Error::MissingGlyph)));
+        assert!(!layer.contains_glyph("nope"));
+        layer.save(dir.path()).unwrap();
+    }
+
     #[test]
     fn delete() {
         let layer_path = "testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs";
@@ -114,8 +520,117 @@ mod tests {
         let mut layer = Layer::load(layer_path).unwrap();
         let mut glyph = Glyph::new_named("A");
         glyph.advance = Some(Advance::Height(69.));
-        layer.set_glyph("A_.glif", glyph);
+        layer.set_glyph(glyph);
         let glyph = layer.get_glyph("A").expect("failed to load glyph 'A'");
         assert_eq!(glyph.advance, Some(Advance::Height(69.)));
     }
+
+    #[test]
+    fn new_glyph_file_names() {
+        let layer_path = "testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs";
+        let mut layer = Layer::load(layer_path).unwrap();
+        layer.set_glyph(Glyph::new_named("A"));
+        layer.set_glyph(Glyph::new_named("a"));
+        layer.set_glyph(Glyph::new_named(".notdef"));
+        assert_eq!(layer.contents.get("A"), Some(&PathBuf::from("A_.glif")));
+        assert_eq!(layer.contents.get("a"), Some(&PathBuf::from("a.glif")));
+        assert_eq!(layer.contents.get(".notdef"), Some(&PathBuf::from("_notdef.glif")));
+
+        // file names of deleted glyphs can be reused
+        layer.set_glyph(Glyph::new_named("a_"));
+        assert_eq!(layer.contents.get("a_"), Some(&PathBuf::from("a_000000000000001.glif")));
+        layer.delete_glyph("A");
+        layer.delete_glyph("a_");
+        layer.set_glyph(Glyph::new_named("a_"));
+        assert_eq!(layer.contents.get("a_"), Some(&PathBuf::from("a_.glif")));
+    }
+
+    #[test]
+    fn normalize_file_names_avoids_unloaded() {
+        let mut layer = Layer::new();
+        layer.contents.insert("a_".into(), "a_.glif".into());
+        layer.contents.insert("A".into(), "foo.glif".into());
+        layer.loaded.insert("A".into(), Entry::Loaded(Box::new(Glyph::new_named("A"))));
+        layer.normalize_file_names();
+        assert_eq!(layer.contents.get("a_"), Some(&PathBuf::from("a_.glif")));
+        assert_eq!(layer.contents.get("A"), Some(&PathBuf::from("A_000000000000001.glif")));
+    }
+
+    #[test]
+    fn save_tracks_contents() {
+        let dir = tempdir::TempDir::new("layer").unwrap();
+        let layer_path = "testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs";
+        let layer = Layer::load(layer_path).unwrap();
+        layer.save(dir.path()).unwrap();
+        assert!(dir.path().join("A_.glif").exists());
+        assert!(dir.path().join("D_.glif").exists());
+
+        let mut layer = Layer::load(dir.path()).unwrap();
+        fs::write(dir.path().join("orphan.glif"), "").unwrap();
+        layer.delete_glyph("A");
+        layer.set_glyph(Glyph::new_named("B"));
+        layer.save(dir.path()).unwrap();
+        assert!(!dir.path().join("A_.glif").exists());
+        assert!(!dir.path().join("orphan.glif").exists());
+        assert!(dir.path().join("B_.glif").exists());
+        assert!(dir.path().join("D_.glif").exists());
+
+        let mut layer = Layer::load(dir.path()).unwrap();
+        assert!(!layer.contains_glyph("A"));
+        assert!(layer.get_glyph("B").is_ok());
+        assert!(layer.get_glyph("D").is_ok());
+    }
+
+    #[test]
+    fn save_errored_glyph() {
+        let dir = tempdir::TempDir::new("layer").unwrap();
+        let source = dir.path().join("source");
+        Layer::load("testdata/mutatorSans/MutatorSansBoldWide.ufo/glyphs")
+            .unwrap()
+            .save(&source)
+            .unwrap();
+        fs::write(source.join("A_.glif"), "<glyph>").unwrap();
+
+        let mut layer = Layer::load(&source).unwrap();
+        assert!(layer.get_glyph("A").is_err());
+        let target = dir.path().join("target");
+        assert!(matches!(layer.save(&target), Err(Error::SavedError(_))));
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn layer_info() {
+        let dir = tempdir::TempDir::new("glyphs.background").unwrap();
+        let mut layer = Layer::load("testdata/kerningtest.ufo/glyphs.background").unwrap();
+        assert_eq!(layer.color, Some(Color { red: 0., green: 0.5, blue: 1., alpha: 0.25 }));
+        let guidelines = layer.guidelines.as_ref().unwrap();
+        assert_eq!(guidelines.len(), 2);
+        assert_eq!(guidelines[0].line, Line::Horizontal(700.));
+        assert_eq!(guidelines[1].line, Line::Angle { x: 10., y: 20.5, degrees: 45. });
+        assert_eq!(guidelines[1].name.as_deref(), Some("slant"));
+        let lib = layer.lib.as_ref().unwrap();
+        assert_eq!(lib.get("com.example.visible"), Some(&Value::Boolean(false)));
+
+        layer.save(dir.path()).unwrap();
+        let loaded = Layer::load(dir.path()).unwrap();
+        assert_eq!(loaded.color, layer.color);
+        assert_eq!(loaded.guidelines, layer.guidelines);
+        assert_eq!(loaded.lib, layer.lib);
+
+        layer.color = None;
+        layer.guidelines = None;
+        layer.lib = None;
+        layer.save(dir.path()).unwrap();
+        assert!(!dir.path().join("layerinfo.plist").exists());
+
+        // a guideline without coordinates is an error, not silently dropped
+        let info = fs::read_to_string("testdata/kerningtest.ufo/glyphs.background/layerinfo.plist")
+            .unwrap()
+            .replace(
+                "<key>y</key>\n\t\t\t\t<integer>700</integer>",
+                "<key>name</key><string>x</string>",
+            );
+        fs::write(dir.path().join("layerinfo.plist"), info).unwrap();
+        assert!(matches!(Layer::load(dir.path()), Err(Error::PlistError(_))));
+    }
 }
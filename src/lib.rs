@@ -9,17 +9,51 @@
 //!
 //! let path = "RoflsSansLight.ufo";
 //! let mut font_obj = Ufo::load(path).expect("failed to load font");
-//! let layer = font_obj.find_layer(|layer| layer.name == "glyphs").unwrap();
+//! let layer = font_obj.default_layer_mut();
 //! let glyph_a = layer.get_glyph("A").expect("missing glyph");
 //! assert_eq!(glyph_a.name.as_str(), "A");
 //! ```
 
+mod datastore;
+mod downconversion;
 pub mod error;
+pub mod features;
+pub mod fontinfo;
 pub mod glyph;
+pub mod groups;
+pub mod kerning;
 mod layer;
+mod names;
+mod normalize;
 mod ufo;
+mod upconversion;
+mod write;
 
+pub use datastore::{DataStore, ImageStore};
+pub use downconversion::DataLoss;
 pub use error::Error;
-pub use glyph::Glyph;
+pub use fontinfo::FontInfo;
+pub use glyph::{Glyph, Plist};
+pub use groups::Groups;
+pub use kerning::Kerning;
 pub use layer::Layer;
-pub use ufo::Ufo;
+pub use names::user_name_to_file_name;
+pub use normalize::{normalize, NormalizeOptions};
+pub use ufo::{
+    FormatVersion, LoadOptions, MetaInfo, MissingImage, OpenTypeCategory, SaveOptions, Ufo,
+};
+pub use upconversion::KerningGroupRenames;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(Ufo: Send, Sync);
+    assert_impl_all!(Layer: Send, Sync);
+    assert_impl_all!(Glyph: Send, Sync);
+    assert_impl_all!(Error: Send, Sync);
+    assert_impl_all!(FontInfo: Send, Sync);
+    assert_impl_all!(Groups: Send, Sync);
+    assert_impl_all!(Kerning: Send, Sync);
+}
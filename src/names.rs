@@ -0,0 +1,184 @@
+//! Converting glyph and layer names to file names.
+
+/// The maximum length, in bytes, of a file name.
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// The number of digits used when appending a number to resolve a clash.
+const CLASH_SUFFIX_DIGITS: usize = 15;
+
+/// Names that are reserved on some file systems, in lowercase.
+static RESERVED_FILE_NAMES: &[&str] =
+    &["con", "prn", "aux", "clock$", "nul", "com1", "com2", "com3", "com4", "lpt1", "lpt2", "lpt3"];
+
+/// Returns a file name for `name`, following the [user name to file name
+/// convention][convention] described in the UFO spec.
+///
+/// Illegal characters are replaced with `_`, uppercase letters are followed
+/// by `_`, names reserved on Windows are prefixed with `_`, and the result
+/// (including `prefix` and `suffix`) is limited to 255 bytes.
+///
+/// `existing` is called with the lowercased version of a candidate file name,
+/// and should return `true` if that name is already in use. If it is, a
+/// fifteen digit number is appended to the name until the name is unique.
+///
+/// # Examples
+///
+/// ```
+/// use norad::user_name_to_file_name;
+///
+/// let name = user_name_to_file_name("Aacute_V.swash", "", ".glif", |_| false);
+/// assert_eq!(name, "A_acute_V_.swash.glif");
+/// ```
+///
+/// [convention]: http://unifiedfontobject.org/versions/ufo3/conventions/#common-user-name-to-file-name-algorithm
+pub fn user_name_to_file_name(
+    name: &str,
+    prefix: &str,
+    suffix: &str,
+    existing: impl Fn(&str) -> bool,
+) -> String {
+    let mut result = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        match c {
+            '.' if i == 0 && prefix.is_empty() => result.push('_'),
+            c if is_illegal(c) => result.push('_'),
+            c if c.is_uppercase() => {
+                result.push(c);
+                result.push('_');
+            }
+            c => result.push(c),
+        }
+    }
+
+    let mut result = result
+        .split('.')
+        .map(|part| {
+            if RESERVED_FILE_NAMES.contains(&part.to_lowercase().as_str()) {
+                format!("_{}", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+
+    let max_len = MAX_FILE_NAME_LENGTH.saturating_sub(prefix.len() + suffix.len());
+    truncate_to_char_boundary(&mut result, max_len);
+
+    let full_name = format!("{}{}{}", prefix, result, suffix);
+    if !existing(&full_name.to_lowercase()) {
+        return full_name;
+    }
+    handle_clash(result, prefix, suffix, existing)
+}
+
+/// Append increasing numbers to `name` until it no longer clashes.
+fn handle_clash(
+    mut name: String,
+    prefix: &str,
+    suffix: &str,
+    existing: impl Fn(&str) -> bool,
+) -> String {
+    let max_len =
+        MAX_FILE_NAME_LENGTH.saturating_sub(prefix.len() + suffix.len() + CLASH_SUFFIX_DIGITS);
+    truncate_to_char_boundary(&mut name, max_len);
+
+    for counter in 1u64.. {
+        let full_name =
+            format!("{}{}{:0width$}{}", prefix, name, counter, suffix, width = CLASH_SUFFIX_DIGITS);
+        if !existing(&full_name.to_lowercase()) {
+            return full_name;
+        }
+    }
+    unreachable!("exhausted all possible file names")
+}
+
+fn is_illegal(c: char) -> bool {
+    match c {
+        '"' | '*' | '+' | '/' | ':' | '<' | '>' | '?' | '[' | '\\' | ']' | '|' => true,
+        c => c.is_control(),
+    }
+}
+
+/// Truncate `s` to at most `len` bytes, without splitting a character.
+fn truncate_to_char_boundary(s: &mut String, len: usize) {
+    if s.len() <= len {
+        return;
+    }
+    let mut len = len;
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    s.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_name(name: &str) -> String {
+        user_name_to_file_name(name, "", ".glif", |_| false)
+    }
+
+    #[test]
+    fn spec_examples() {
+        assert_eq!(file_name("a"), "a.glif");
+        assert_eq!(file_name("A"), "A_.glif");
+        assert_eq!(file_name("AE"), "A_E_.glif");
+        assert_eq!(file_name("Ae"), "A_e.glif");
+        assert_eq!(file_name("ae"), "ae.glif");
+        assert_eq!(file_name("aE"), "aE_.glif");
+        assert_eq!(file_name("a.alt"), "a.alt.glif");
+        assert_eq!(file_name("A.alt"), "A_.alt.glif");
+        assert_eq!(file_name("A.Alt"), "A_.A_lt.glif");
+        assert_eq!(file_name("A.aLt"), "A_.aL_t.glif");
+        assert_eq!(file_name("A.alT"), "A_.alT_.glif");
+        assert_eq!(file_name("T_H"), "T__H_.glif");
+        assert_eq!(file_name("T_h"), "T__h.glif");
+        assert_eq!(file_name("t_h"), "t_h.glif");
+        assert_eq!(file_name("F_F_I"), "F__F__I_.glif");
+        assert_eq!(file_name("f_f_i"), "f_f_i.glif");
+        assert_eq!(file_name("Aacute_V.swash"), "A_acute_V_.swash.glif");
+        assert_eq!(file_name(".notdef"), "_notdef.glif");
+        assert_eq!(file_name("con"), "_con.glif");
+        assert_eq!(file_name("CON"), "C_O_N_.glif");
+        assert_eq!(file_name("con.alt"), "_con.alt.glif");
+        assert_eq!(file_name("alt.con"), "alt._con.glif");
+    }
+
+    #[test]
+    fn illegal_characters() {
+        assert_eq!(file_name("a/b*c?"), "a_b_c_.glif");
+        assert_eq!(file_name("a\u{7}b"), "a_b.glif");
+        assert_eq!(user_name_to_file_name(".notdef", "glyphs", "", |_| false), "glyphs.notdef");
+    }
+
+    #[test]
+    fn long_names() {
+        let name = "a".repeat(300);
+        let result = file_name(&name);
+        assert_eq!(result.len(), 255);
+        assert!(result.ends_with(".glif"));
+
+        let name = "é".repeat(200);
+        let result = file_name(&name);
+        assert!(result.len() <= 255);
+
+        let name = format!("con.{}", "a".repeat(250));
+        let result = file_name(&name);
+        assert_eq!(result.len(), 255);
+        assert!(result.starts_with("_con.aaa"));
+    }
+
+    #[test]
+    fn clashes() {
+        let existing = ["a_.glif", "a_000000000000001.glif"];
+        let result = user_name_to_file_name("A", "", ".glif", |n| existing.contains(&n));
+        assert_eq!(result, "A_000000000000002.glif");
+
+        let name = "a".repeat(300);
+        let result = user_name_to_file_name(&name, "", ".glif", |n| !n.ends_with("01.glif"));
+        assert_eq!(result.len(), 255);
+        assert!(result.ends_with("000000000000001.glif"));
+    }
+}
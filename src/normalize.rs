@@ -0,0 +1,227 @@
+//! Rewriting a UFO in canonical form.
+
+use std::path::Path;
+
+use crate::glyph::{Advance, AffineTransform, Glyph, Line, OutlineElement};
+use crate::{Error, FormatVersion, SaveOptions, Ufo};
+
+/// Options for [`normalize`](fn.normalize.html).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizeOptions {
+    /// The number of decimal places to round glyph coordinates to, or `None`
+    /// to leave them as they are.
+    ///
+    /// Numbers are never written with more than ten decimal places, so larger
+    /// values are treated as ten.
+    pub float_precision: Option<u32>,
+}
+
+/// Load the UFO at `path` and write it back in canonical form.
+///
+/// Everything is written the way [ufoNormalizer] writes it. In addition:
+///
+/// - glyph coordinates are rounded, if `options` asks for it;
+/// - empty glyph libs and outlines, and empty font and layer libs, are removed;
+/// - glyph file names are derived from glyph names, using
+///   [`user_name_to_file_name`];
+/// - `.glif` files that don't belong to a glyph, and files in the `images`
+///   directory that no glyph uses, are deleted.
+///
+/// UFO3 fonts are written as UFO3. Older fonts are written as UFO2.
+///
+/// # Examples
+///
+/// ```no_run
+/// use norad::{normalize, NormalizeOptions};
+///
+/// let options = NormalizeOptions { float_precision: Some(3) };
+/// normalize("fonts/Rofls.ufo", &options).expect("failed to normalize");
+/// ```
+///
+/// [ufoNormalizer]: https://github.com/unified-font-object/ufoNormalizer
+/// [`user_name_to_file_name`]: fn.user_name_to_file_name.html
+pub fn normalize<P: AsRef<Path>>(path: P, options: &NormalizeOptions) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut ufo = Ufo::load(path)?;
+
+    for layer in ufo.layers.iter_mut() {
+        let layer = &mut layer.layer;
+        let names: Vec<String> = layer.glyph_names().map(String::from).collect();
+        for name in names {
+            let mut glyph = layer.get_glyph(&name)?.clone();
+            normalize_glyph(&mut glyph, options);
+            layer.set_glyph(glyph);
+        }
+        layer.normalize_file_names();
+        if layer.lib.as_ref().map(|lib| lib.is_empty()).unwrap_or(false) {
+            layer.lib = None;
+        }
+    }
+    if ufo.lib.as_ref().map(|lib| lib.is_empty()).unwrap_or(false) {
+        ufo.lib = None;
+    }
+
+    let format_version = match ufo.meta.format_version {
+        FormatVersion::V3 => FormatVersion::V3,
+        _ => FormatVersion::V2,
+    };
+    ufo.save_with_options(path, &SaveOptions { format_version })?;
+    Ok(())
+}
+
+fn normalize_glyph(glyph: &mut Glyph, options: &NormalizeOptions) {
+    if glyph.lib.as_ref().map(|lib| lib.is_empty()).unwrap_or(false) {
+        glyph.lib = None;
+    }
+    if let Some(outline) = glyph.outline.as_mut() {
+        outline.elements.retain(|element| match element {
+            OutlineElement::Contour(contour) => !contour.points.is_empty(),
+            OutlineElement::Component(_) => true,
+        });
+    }
+    if glyph.outline.as_ref().map(|o| o.elements.is_empty()).unwrap_or(false) {
+        glyph.outline = None;
+    }
+    if let Some(precision) = options.float_precision {
+        round_glyph(glyph, precision);
+    }
+}
+
+/// The most decimal places `format_number` writes.
+const MAX_PRECISION: u32 = 10;
+
+fn round_glyph(glyph: &mut Glyph, precision: u32) {
+    let factor = 10f64.powi(precision.min(MAX_PRECISION) as i32);
+    let round = |value: &mut f32| *value = ((*value as f64 * factor).round() / factor) as f32;
+
+    if let Some(advance) = glyph.advance.as_mut() {
+        match advance {
+            Advance::Width(value) | Advance::Height(value) => round(value),
+        }
+    }
+    for guideline in glyph.guidelines.iter_mut().flatten() {
+        match &mut guideline.line {
+            Line::Vertical(x) => round(x),
+            Line::Horizontal(y) => round(y),
+            Line::Angle { x, y, degrees } => {
+                round(x);
+                round(y);
+                round(degrees);
+            }
+        }
+    }
+    for anchor in glyph.anchors.iter_mut().flatten() {
+        round(&mut anchor.x);
+        round(&mut anchor.y);
+    }
+    for element in glyph.outline.iter_mut().flat_map(|o| o.elements.iter_mut()) {
+        match element {
+            OutlineElement::Component(component) => {
+                round_transform(&mut component.transform, round)
+            }
+            OutlineElement::Contour(contour) => {
+                for point in contour.points.iter_mut() {
+                    round(&mut point.x);
+                    round(&mut point.y);
+                }
+            }
+        }
+    }
+    if let Some(image) = glyph.image.as_mut() {
+        round_transform(&mut image.transform, round);
+    }
+}
+
+fn round_transform(transform: &mut AffineTransform, round: impl Fn(&mut f32)) {
+    round(&mut transform.x_scale);
+    round(&mut transform.xy_scale);
+    round(&mut transform.yx_scale);
+    round(&mut transform.y_scale);
+    round(&mut transform.x_offset);
+    round(&mut transform.y_offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::{Contour, ContourPoint, Outline, PointType};
+    use std::fs;
+
+    #[test]
+    fn round_coordinates() {
+        let mut glyph = Glyph::new_named("a");
+        let mut outline = Outline::default();
+        let point = ContourPoint {
+            name: None,
+            x: 10.126,
+            y: -0.0004,
+            typ: PointType::Line,
+            smooth: false,
+            identifier: None,
+        };
+        outline.push_contour(Contour { identifier: None, points: vec![point] });
+        outline.push_contour(Contour { identifier: None, points: Vec::new() });
+        glyph.outline = Some(outline);
+        glyph.lib = Some(Default::default());
+
+        normalize_glyph(&mut glyph, &NormalizeOptions { float_precision: Some(2) });
+        assert_eq!(glyph.lib, None);
+        let contours: Vec<_> = glyph.outline.as_ref().unwrap().contours().collect();
+        assert_eq!(contours.len(), 1);
+        assert_eq!((contours[0].points[0].x, contours[0].points[0].y), (10.13, 0.));
+
+        let mut precise = glyph.clone();
+        normalize_glyph(&mut precise, &NormalizeOptions { float_precision: Some(u32::MAX) });
+        assert_eq!(precise, glyph);
+
+        glyph.outline.as_mut().unwrap().elements.clear();
+        normalize_glyph(&mut glyph, &NormalizeOptions::default());
+        assert_eq!(glyph.outline, None);
+    }
+
+    #[test]
+    fn normalize_font() {
+        let source = Path::new("testdata/normalizedtest.ufo");
+        let dir = tempdir::TempDir::new("Normalize.ufo").unwrap();
+        copy_dir(source, dir.path());
+
+        // store a glyph under a non-canonical file name, and add a stray
+        // glyph file and an unused image
+        let glyphs = dir.path().join("glyphs");
+        let contents = fs::read_to_string(glyphs.join("contents.plist")).unwrap();
+        let contents = contents.replace("<string>A_.glif</string>", "<string>a.glif</string>");
+        fs::write(glyphs.join("contents.plist"), contents).unwrap();
+        fs::rename(glyphs.join("A_.glif"), glyphs.join("a.glif")).unwrap();
+        fs::write(glyphs.join("orphan.glif"), "").unwrap();
+        let images = dir.path().join("images");
+        fs::create_dir(&images).unwrap();
+        fs::write(images.join("period sketch.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        fs::write(images.join("unused.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+
+        normalize(dir.path(), &NormalizeOptions::default()).unwrap();
+        assert!(!glyphs.join("a.glif").exists());
+        assert!(!glyphs.join("orphan.glif").exists());
+        assert!(images.join("period sketch.png").exists());
+        assert!(!images.join("unused.png").exists());
+        fs::remove_dir_all(images).unwrap();
+
+        for file in ["glyphs/contents.plist", "glyphs/A_.glif", "glyphs/A_acute.glif"].iter() {
+            let expected = fs::read_to_string(source.join(file)).unwrap();
+            let written = fs::read_to_string(dir.path().join(file)).unwrap();
+            assert_eq!(written, expected, "{} differs", file);
+        }
+    }
+
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &target);
+            } else {
+                fs::copy(&path, &target).unwrap();
+            }
+        }
+    }
+}
@@ -1,15 +1,123 @@
 //! Reading and (maybe) writing Unified Font Object files.
 
-use crate::layer::Layer;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use plist::Value;
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::datastore::{DataStore, ImageStore};
+use crate::downconversion::{self, DataLoss};
+use crate::error::GlyphError;
+use crate::fontinfo::FontInfo;
+use crate::glyph::Plist;
+use crate::groups::Groups;
+use crate::kerning::Kerning;
+use crate::layer::Layer;
+use crate::names::user_name_to_file_name;
+use crate::upconversion::{self, KerningGroupRenames};
+use crate::write;
 use crate::Error;
 
 static LAYER_CONTENTS_FILE: &str = "layercontents.plist";
+static METAINFO_FILE: &str = "metainfo.plist";
+static FONTINFO_FILE: &str = "fontinfo.plist";
+static GROUPS_FILE: &str = "groups.plist";
+static KERNING_FILE: &str = "kerning.plist";
+static FEATURES_FILE: &str = "features.fea";
+static LIB_FILE: &str = "lib.plist";
+static DATA_DIR: &str = "data";
+static IMAGES_DIR: &str = "images";
+static DEFAULT_METAINFO_CREATOR: &str = "org.linebender.norad";
+static DEFAULT_LAYER_NAME: &str = "public.default";
+static DEFAULT_GLYPHS_DIRNAME: &str = "glyphs";
+static LAYER_DIRNAME_PREFIX: &str = "glyphs.";
+
+static PUBLIC_GLYPH_ORDER_KEY: &str = "public.glyphOrder";
+static PUBLIC_POSTSCRIPT_NAMES_KEY: &str = "public.postscriptNames";
+static PUBLIC_OPENTYPE_CATEGORIES_KEY: &str = "public.openTypeCategories";
+static PUBLIC_SKIP_EXPORT_GLYPHS_KEY: &str = "public.skipExportGlyphs";
+static PUBLIC_UNICODE_VARIATION_SEQUENCES_KEY: &str = "public.unicodeVariationSequences";
 
 /// A Unified Font Object.
 pub struct Ufo {
-    layers: Vec<LayerInfo>,
+    /// The contents of `metainfo.plist` when this font was loaded.
+    pub meta: MetaInfo,
+    pub(crate) layers: Vec<LayerInfo>,
+    pub font_info: Option<FontInfo>,
+    pub groups: Option<Groups>,
+    pub kerning: Option<Kerning>,
+    /// The contents of `features.fea`, without any `include()` statements
+    /// resolved. See [`features::resolve_includes`].
+    ///
+    /// [`features::resolve_includes`]: features/fn.resolve_includes.html
+    pub features: Option<String>,
+    /// The contents of `lib.plist`.
+    pub lib: Option<Plist>,
+    /// The kerning groups that were given `public.kern1.` and `public.kern2.`
+    /// names when this font was converted from UFO1 or UFO2.
+    pub kerning_group_renames: Option<KerningGroupRenames>,
+    /// The files in the `data` directory.
+    pub data: DataStore,
+    /// The PNG images in the `images` directory.
+    pub images: ImageStore,
+}
+
+/// A glyph whose image is not in the font's [`ImageStore`].
+///
+/// [`ImageStore`]: struct.ImageStore.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingImage {
+    /// The name of the layer containing the glyph.
+    pub layer: String,
+    /// The name of the glyph.
+    pub glyph: String,
+    /// The file name the glyph refers to.
+    pub file_name: PathBuf,
+}
+
+/// Options for [`Ufo::save_with_options`].
+///
+/// [`Ufo::save_with_options`]: struct.Ufo.html#method.save_with_options
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveOptions {
+    /// The version of the UFO format to write. Only UFO2 and UFO3 can be written.
+    pub format_version: FormatVersion,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        SaveOptions { format_version: FormatVersion::V3 }
+    }
+}
+
+/// Options for [`Ufo::load_with`].
+///
+/// [`Ufo::load_with`]: struct.Ufo.html#method.load_with
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadOptions {
+    /// Load every glyph of every layer up front, instead of when it is first
+    /// requested. With the `rayon` feature, glyphs are parsed in parallel.
+    pub load_glyphs: bool,
+    /// Keep glyphs in GLIF format 1 as they were written, instead of
+    /// converting them to the format 2 model. See [`Glyph::convert_to_v2`].
+    ///
+    /// [`Glyph::convert_to_v2`]: struct.Glyph.html#method.convert_to_v2
+    pub keep_raw_glyphs: bool,
+}
+
+/// The OpenType category of a glyph, as stored in `public.openTypeCategories`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenTypeCategory {
+    Unassigned,
+    Base,
+    Ligature,
+    Mark,
+    Component,
 }
 
 /// A [font layer], along with its name and path.
@@ -23,25 +131,650 @@ pub struct LayerInfo {
     pub layer: Layer,
 }
 
+/// The contents of the [`metainfo.plist`] file.
+///
+/// [`metainfo.plist`]: http://unifiedfontobject.org/versions/ufo3/metainfo.plist/
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaInfo {
+    /// The application or library that wrote the font, in reverse domain
+    /// notation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    pub format_version: FormatVersion,
+}
+
+/// A version of the UFO format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FormatVersion {
+    V1 = 1,
+    V2 = 2,
+    V3 = 3,
+}
+
+/// `metainfo.plist` as it is stored on disk, before the format version is checked.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMetaInfo {
+    creator: Option<String>,
+    format_version: u32,
+}
+
+impl Default for MetaInfo {
+    fn default() -> Self {
+        MetaInfo {
+            creator: Some(DEFAULT_METAINFO_CREATOR.to_string()),
+            format_version: FormatVersion::V3,
+        }
+    }
+}
+
+impl MetaInfo {
+    fn load(path: &Path) -> Result<MetaInfo, Error> {
+        if !path.exists() {
+            return Err(Error::MissingFile(METAINFO_FILE));
+        }
+        let raw: RawMetaInfo = plist::from_file(path)?;
+        let format_version = match raw.format_version {
+            1 => FormatVersion::V1,
+            2 => FormatVersion::V2,
+            3 => FormatVersion::V3,
+            other => return Err(Error::UnsupportedFormatVersion(other)),
+        };
+        Ok(MetaInfo { creator: raw.creator, format_version })
+    }
+}
+
+impl Serialize for FormatVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(*self as u32)
+    }
+}
+
 impl Ufo {
+    /// Create a new font object with an empty default layer and empty font
+    /// info, that is not backed by any files.
+    ///
+    /// The font is written as UFO3 when it is saved, and its `metainfo.plist`
+    /// names this crate as the creator.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use norad::{Glyph, Ufo};
+    ///
+    /// let mut ufo = Ufo::new();
+    /// ufo.default_layer_mut().set_glyph(Glyph::new_named("A"));
+    /// ufo.save("fonts/New.ufo").expect("failed to save");
+    /// ```
+    pub fn new() -> Self {
+        let default_layer = LayerInfo {
+            name: DEFAULT_LAYER_NAME.into(),
+            path: DEFAULT_GLYPHS_DIRNAME.into(),
+            layer: Layer::new(),
+        };
+        Ufo {
+            meta: MetaInfo::default(),
+            layers: vec![default_layer],
+            font_info: Some(FontInfo::default()),
+            groups: None,
+            kerning: None,
+            features: None,
+            lib: None,
+            kerning_group_renames: None,
+            data: DataStore::new(),
+            images: ImageStore::new(),
+        }
+    }
+
     /// Attempt to load a font object from a file. `path` must point to
     /// a directory with the structure described in [v3 of the Unified Font Object][v3]
     /// spec.
     ///
+    /// UFO1 and UFO2 fonts are converted to the UFO3 model as they are loaded:
+    /// the glyphs directory becomes the default layer, `fontinfo.plist` keys
+    /// are renamed and converted, and kerning groups are given UFO3 names.
+    /// The renamed groups are recorded in `kerning_group_renames`.
+    ///
     /// [v3]: http://unifiedfontobject.org/versions/ufo3/
     pub fn load<P: Into<PathBuf>>(path: P) -> Result<Ufo, Error> {
         let path = path.into();
-        let contents_path = path.join(LAYER_CONTENTS_FILE);
-        let mut contents: Vec<(String, PathBuf)> = plist::from_file(contents_path)?;
-        let layers: Result<Vec<LayerInfo>, Error> = contents
+        let meta = MetaInfo::load(&path.join(METAINFO_FILE))?;
+        let is_v3 = meta.format_version == FormatVersion::V3;
+
+        let mut contents: Vec<(String, PathBuf)> = if is_v3 {
+            plist::from_file(path.join(LAYER_CONTENTS_FILE))?
+        } else {
+            vec![(DEFAULT_LAYER_NAME.into(), DEFAULT_GLYPHS_DIRNAME.into())]
+        };
+        let layers: Vec<LayerInfo> = contents
             .drain(..)
             .map(|(name, p)| {
                 let layer_path = path.join(&p);
-                let layer = Layer::load(layer_path)?;
+                let mut layer = Layer::load(layer_path)?;
+                layer.source_format = meta.format_version;
                 Ok(LayerInfo { name, path: p, layer })
             })
-            .collect();
-        Ok(Ufo { layers: layers? })
+            .collect::<Result<_, Error>>()?;
+        if !layers.iter().any(|l| l.path == Path::new(DEFAULT_GLYPHS_DIRNAME)) {
+            return Err(Error::MissingDefaultLayer);
+        }
+
+        let font_info = if is_v3 {
+            load_optional_plist(&path.join(FONTINFO_FILE))?
+        } else {
+            load_optional_dict(&path.join(FONTINFO_FILE))?
+                .map(|info| upconversion::upconvert_font_info(info, meta.format_version))
+                .transpose()?
+        };
+        if let Some(font_info) = font_info.as_ref() {
+            font_info.validate()?;
+        }
+
+        let mut groups: Option<Groups> = load_optional_plist(&path.join(GROUPS_FILE))?;
+        let mut kerning: Option<Kerning> = load_optional_plist(&path.join(KERNING_FILE))?;
+        let mut kerning_group_renames = None;
+        if !is_v3 {
+            if let Some(old_groups) = groups.as_ref() {
+                let default_layer = &layers[0].layer;
+                let (new_kerning, new_groups, renames) = upconversion::upconvert_kerning(
+                    kerning.as_ref().unwrap_or(&Kerning::new()),
+                    old_groups,
+                    |name| default_layer.contains_glyph(name),
+                );
+                groups = Some(new_groups);
+                kerning = kerning.map(|_| new_kerning);
+                kerning_group_renames = Some(renames);
+            }
+        }
+        if let Some(groups) = groups.as_ref() {
+            groups.validate().map_err(Error::InvalidGroups)?;
+        }
+
+        let features_path = path.join(FEATURES_FILE);
+        let features =
+            if features_path.exists() { Some(fs::read_to_string(features_path)?) } else { None };
+
+        let lib = load_optional_dict(&path.join(LIB_FILE))?;
+
+        let data_path = path.join(DATA_DIR);
+        let data = if is_v3 && data_path.exists() {
+            DataStore::load(&data_path)?
+        } else {
+            DataStore::new()
+        };
+        let images_path = path.join(IMAGES_DIR);
+        let images = if is_v3 && images_path.exists() {
+            ImageStore::load(&images_path)?
+        } else {
+            ImageStore::new()
+        };
+        Ok(Ufo {
+            meta,
+            layers,
+            font_info,
+            groups,
+            kerning,
+            features,
+            lib,
+            kerning_group_renames,
+            data,
+            images,
+        })
+    }
+
+    /// Attempt to load a font object from a file, as described by `options`.
+    ///
+    /// When glyphs are loaded eagerly, every glyph is parsed even if some of
+    /// them fail, and all failures are returned together as
+    /// [`Error::GlyphErrors`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use norad::{LoadOptions, Ufo};
+    ///
+    /// let options = LoadOptions { load_glyphs: true, ..Default::default() };
+    /// let ufo = Ufo::load_with("fonts/Rofls.ufo", &options).expect("failed to load");
+    /// ```
+    ///
+    /// [`Error::GlyphErrors`]: enum.Error.html#variant.GlyphErrors
+    pub fn load_with<P: Into<PathBuf>>(path: P, options: &LoadOptions) -> Result<Ufo, Error> {
+        let mut ufo = Ufo::load(path)?;
+        for layer in ufo.layers.iter_mut() {
+            layer.layer.keep_raw_glyphs = options.keep_raw_glyphs;
+        }
+        if options.load_glyphs {
+            let mut errors = Vec::new();
+            for layer in ufo.layers.iter_mut() {
+                errors.extend(
+                    layer.layer.load_all_glyphs().into_iter().map(|(glyph, error)| GlyphError {
+                        layer: layer.name.clone(),
+                        glyph,
+                        error,
+                    }),
+                );
+            }
+            if !errors.is_empty() {
+                return Err(Error::GlyphErrors(errors));
+            }
+        }
+        Ok(ufo)
+    }
+
+    /// Attempt to save this font object to the directory at `path`.
+    ///
+    /// This writes `metainfo.plist`, `fontinfo.plist`, `groups.plist`,
+    /// `kerning.plist`, `features.fea`, `lib.plist` and `layercontents.plist`,
+    /// as well as the `contents.plist` and `.glif` files of each layer and
+    /// the files in the `data` and `images` directories. The directory is
+    /// created if it does not exist; optional files that are `None` are
+    /// removed if present.
+    ///
+    /// Images that no glyph in any layer refers to are not written, and are
+    /// deleted if present. To find out which images are in use, glyphs that
+    /// have not been loaded are read from disk.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.save_with_options(path, &SaveOptions::default())?;
+        Ok(())
+    }
+
+    /// Attempt to save this font object to the directory at `path`, in the
+    /// format described by `options`.
+    ///
+    /// When saving as UFO2, only the default layer is written, glyphs are
+    /// written as GLIF format 1 with anchors stored as single-point contours,
+    /// and kerning groups that were renamed when the font was loaded get
+    /// their original names back. Everything that UFO2 can't express is
+    /// left out and returned as a list of [`DataLoss`]; this list is always
+    /// empty when saving as UFO3.
+    ///
+    /// [`DataLoss`]: enum.DataLoss.html
+    pub fn save_with_options<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &SaveOptions,
+    ) -> Result<Vec<DataLoss>, Error> {
+        let path = path.as_ref();
+        let format = options.format_version;
+        if format == FormatVersion::V1 {
+            return Err(Error::UnsupportedFormatVersion(format as u32));
+        }
+        if let Some(groups) = self.groups.as_ref() {
+            groups.validate().map_err(Error::InvalidGroups)?;
+        }
+
+        let mut losses = Vec::new();
+        let mut font_info = self.font_info.clone();
+        let mut groups = self.groups.clone();
+        let mut kerning = self.kerning.clone();
+        if format == FormatVersion::V2 {
+            font_info = font_info.map(|info| downconversion::font_info_to_v2(&info, &mut losses));
+            if let Some(renames) = self.kerning_group_renames.as_ref() {
+                let (new_kerning, new_groups) = downconversion::kerning_to_v2(
+                    self.kerning.as_ref(),
+                    self.groups.as_ref(),
+                    renames,
+                );
+                kerning = new_kerning;
+                groups = new_groups;
+            }
+        }
+
+        // prepare the glyphs before writing anything, so that a failed save
+        // leaves the font intact
+        let is_saved = |layer: &LayerInfo| {
+            format == FormatVersion::V3 || layer.path == Path::new(DEFAULT_GLYPHS_DIRNAME)
+        };
+        let mut layer_glyphs = Vec::new();
+        for layer in self.layers.iter().filter(|l| is_saved(l)) {
+            layer_glyphs.push(layer.layer.glyphs_to_write(format, &mut losses)?);
+        }
+
+        fs::create_dir_all(path)?;
+        let meta = MetaInfo { format_version: format, ..MetaInfo::default() };
+        write::write_plist(&path.join(METAINFO_FILE), &meta)?;
+        save_optional_plist(&path.join(FONTINFO_FILE), font_info.as_ref())?;
+        save_optional_plist(&path.join(GROUPS_FILE), groups.as_ref())?;
+        save_optional_plist(&path.join(KERNING_FILE), kerning.as_ref())?;
+
+        let features_path = path.join(FEATURES_FILE);
+        match self.features.as_ref() {
+            Some(features) => fs::write(features_path, features)?,
+            None if features_path.exists() => fs::remove_file(features_path)?,
+            None => (),
+        }
+
+        let lib_path = path.join(LIB_FILE);
+        match self.lib.as_ref() {
+            Some(lib) => write::write_plist_value(&lib_path, &Value::Dictionary(lib.clone()))?,
+            None if lib_path.exists() => fs::remove_file(lib_path)?,
+            None => (),
+        }
+
+        if format == FormatVersion::V3 {
+            self.data.save(&path.join(DATA_DIR))?;
+        } else if !self.data.is_empty() {
+            losses.push(DataLoss::Data);
+        }
+
+        if format == FormatVersion::V3 {
+            let mut in_use = HashSet::new();
+            if !self.images.is_empty() {
+                for layer in self.layers.iter() {
+                    let references = layer.layer.image_references()?;
+                    in_use.extend(references.into_iter().map(|(_, image)| image));
+                }
+            }
+            self.images.save(&path.join(IMAGES_DIR), |name| in_use.contains(name))?;
+        } else if !self.images.is_empty() {
+            losses.push(DataLoss::Images);
+        }
+
+        // the directories of layers that have since been renamed or removed
+        let contents_path = path.join(LAYER_CONTENTS_FILE);
+        let mut stale_layer_dirs = Vec::new();
+        if format == FormatVersion::V3 {
+            if contents_path.exists() {
+                let old_contents: Vec<(String, PathBuf)> = plist::from_file(&contents_path)?;
+                stale_layer_dirs.extend(
+                    old_contents
+                        .into_iter()
+                        .map(|(_, dir)| dir)
+                        .filter(|dir| !self.layers.iter().any(|l| &l.path == dir)),
+                );
+            }
+        } else {
+            for entry in fs::read_dir(path)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() {
+                    stale_layer_dirs.push(PathBuf::from(entry.file_name()));
+                }
+            }
+        }
+
+        let mut layer_glyphs = layer_glyphs.iter();
+        for layer in self.layers.iter() {
+            if is_saved(layer) {
+                let glyphs = layer_glyphs.next().expect("glyphs prepared for every saved layer");
+                layer.layer.write_glyphs(&path.join(&layer.path), format, glyphs, &mut losses)?;
+            } else {
+                losses.push(DataLoss::Layer(layer.name.clone()));
+            }
+        }
+        // written after the layers, so it never lists a directory that is missing
+        if format == FormatVersion::V3 {
+            let contents: Vec<(&str, &PathBuf)> =
+                self.layers.iter().map(|l| (l.name.as_str(), &l.path)).collect();
+            write::write_plist(&contents_path, &contents)?;
+        }
+        for dir in stale_layer_dirs.into_iter().filter(|dir| is_layer_dir_name(dir)) {
+            let dir = path.join(dir);
+            if dir.is_dir() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+
+        // remove what an earlier UFO3 save may have left behind
+        if format == FormatVersion::V2 {
+            if contents_path.exists() {
+                fs::remove_file(contents_path)?;
+            }
+            for dir in [DATA_DIR, IMAGES_DIR].iter() {
+                let dir = path.join(dir);
+                if dir.is_dir() {
+                    fs::remove_dir_all(dir)?;
+                }
+            }
+        }
+        Ok(losses)
+    }
+
+    /// Returns every glyph, in every layer, whose image is not in [`images`].
+    ///
+    /// Glyphs that have not been loaded are read from disk, but are not kept.
+    ///
+    /// [`images`]: #structfield.images
+    pub fn missing_images(&self) -> Result<Vec<MissingImage>, Error> {
+        let mut missing = Vec::new();
+        for layer in self.layers.iter() {
+            for (glyph, file_name) in layer.layer.image_references()? {
+                if !self.images.contains(&file_name) {
+                    missing.push(MissingImage {
+                        layer: layer.name.clone(),
+                        glyph: glyph.to_string(),
+                        file_name,
+                    });
+                }
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Rename the glyph `old` to `new` in every layer.
+    ///
+    /// Each renamed glyph gets a file name derived from its new name, and
+    /// references to the glyph are updated: component bases in every layer,
+    /// groups, kerning pairs, and the glyph order, PostScript names,
+    /// OpenType categories, skipped glyphs and Unicode variation sequences in
+    /// the lib.
+    ///
+    /// To find every component, all glyphs in the font are loaded first. If
+    /// any of them fail to load, nothing is changed and
+    /// [`Error::GlyphErrors`] is returned. Returns [`Error::MissingGlyph`] if
+    /// no layer contains `old`, and [`Error::DuplicateGlyph`] if a layer
+    /// already contains `new` or `new` is already used in the groups or
+    /// kerning, since renaming would merge the two glyphs' entries there.
+    ///
+    /// Entries in the lib are renamed even if their values are invalid.
+    ///
+    /// [`Error::GlyphErrors`]: enum.Error.html#variant.GlyphErrors
+    /// [`Error::MissingGlyph`]: enum.Error.html#variant.MissingGlyph
+    /// [`Error::DuplicateGlyph`]: enum.Error.html#variant.DuplicateGlyph
+    pub fn rename_glyph(&mut self, old: &str, new: &str) -> Result<(), Error> {
+        if !self.layers.iter().any(|l| l.layer.contains_glyph(old)) {
+            return Err(Error::MissingGlyph);
+        }
+        if old == new {
+            return Ok(());
+        }
+        if self.layers.iter().any(|l| l.layer.contains_glyph(new))
+            || self.groups.as_ref().map(|g| g.contains_glyph(new)).unwrap_or(false)
+            || self.kerning.as_ref().map(|k| k.contains_glyph(new)).unwrap_or(false)
+        {
+            return Err(Error::DuplicateGlyph(new.into()));
+        }
+
+        let mut errors = Vec::new();
+        for layer in self.layers.iter_mut() {
+            errors.extend(
+                layer.layer.load_all_glyphs().into_iter().map(|(glyph, error)| GlyphError {
+                    layer: layer.name.clone(),
+                    glyph,
+                    error,
+                }),
+            );
+        }
+        if !errors.is_empty() {
+            return Err(Error::GlyphErrors(errors));
+        }
+
+        for layer in self.layers.iter_mut() {
+            layer.layer.rename_glyph(old, new);
+            layer.layer.rename_component_bases(old, new);
+        }
+        if let Some(groups) = self.groups.as_mut() {
+            groups.rename_glyph(old, new);
+        }
+        if let Some(kerning) = self.kerning.as_mut() {
+            kerning.rename_glyph(old, new);
+        }
+
+        for key in [
+            PUBLIC_GLYPH_ORDER_KEY,
+            PUBLIC_SKIP_EXPORT_GLYPHS_KEY,
+            PUBLIC_POSTSCRIPT_NAMES_KEY,
+            PUBLIC_OPENTYPE_CATEGORIES_KEY,
+        ]
+        .iter()
+        {
+            self.rename_in_lib_value(key, old, new);
+        }
+        // the glyph names here are the values of nested dictionaries
+        let sequences = self.lib.as_mut().and_then(|lib| {
+            lib.get_mut(PUBLIC_UNICODE_VARIATION_SEQUENCES_KEY)?.as_dictionary_mut()
+        });
+        for bases in sequences.into_iter().flat_map(|s| s.values_mut()) {
+            let glyphs = bases.as_dictionary_mut().into_iter().flat_map(|b| b.values_mut());
+            for glyph in glyphs.filter(|g| g.as_string() == Some(old)) {
+                *glyph = Value::String(new.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the kerning value for a pair of glyphs, resolving kerning groups.
+    ///
+    /// See [`Kerning::lookup`] for the details of the lookup.
+    ///
+    /// [`Kerning::lookup`]: kerning/struct.Kerning.html#method.lookup
+    pub fn kerning_value(&self, first: &str, second: &str) -> Option<f64> {
+        let empty = Groups::default();
+        let groups = self.groups.as_ref().unwrap_or(&empty);
+        self.kerning.as_ref().and_then(|kerning| kerning.lookup(first, second, groups))
+    }
+
+    /// Returns the glyph names in `public.glyphOrder`, the order in which
+    /// glyphs should be stored in a compiled font.
+    pub fn glyph_order(&self) -> Option<Vec<String>> {
+        self.lib_value(PUBLIC_GLYPH_ORDER_KEY).and_then(string_array)
+    }
+
+    /// Set or remove `public.glyphOrder`.
+    pub fn set_glyph_order(&mut self, order: Option<Vec<String>>) {
+        self.set_lib_value(PUBLIC_GLYPH_ORDER_KEY, order.map(string_array_value));
+    }
+
+    /// Returns the map of glyph names to production names in
+    /// `public.postscriptNames`.
+    pub fn postscript_names(&self) -> Option<BTreeMap<String, String>> {
+        self.lib_value(PUBLIC_POSTSCRIPT_NAMES_KEY).and_then(string_dict)
+    }
+
+    /// Set or remove `public.postscriptNames`.
+    pub fn set_postscript_names(&mut self, names: Option<BTreeMap<String, String>>) {
+        let value = names.map(|names| {
+            Value::Dictionary(names.into_iter().map(|(k, v)| (k, Value::String(v))).collect())
+        });
+        self.set_lib_value(PUBLIC_POSTSCRIPT_NAMES_KEY, value);
+    }
+
+    /// Returns the OpenType categories of glyphs in `public.openTypeCategories`.
+    pub fn open_type_categories(&self) -> Option<BTreeMap<String, OpenTypeCategory>> {
+        let categories = self.lib_value(PUBLIC_OPENTYPE_CATEGORIES_KEY).and_then(string_dict)?;
+        categories.into_iter().map(|(glyph, cat)| Some((glyph, cat.parse().ok()?))).collect()
+    }
+
+    /// Set or remove `public.openTypeCategories`.
+    pub fn set_open_type_categories(
+        &mut self,
+        categories: Option<BTreeMap<String, OpenTypeCategory>>,
+    ) {
+        let value = categories.map(|categories| {
+            let dict = categories
+                .into_iter()
+                .map(|(glyph, cat)| (glyph, Value::String(cat.as_str().into())))
+                .collect();
+            Value::Dictionary(dict)
+        });
+        self.set_lib_value(PUBLIC_OPENTYPE_CATEGORIES_KEY, value);
+    }
+
+    /// Returns the glyphs in `public.skipExportGlyphs`, which should not be
+    /// included in a compiled font.
+    pub fn skip_export_glyphs(&self) -> Option<Vec<String>> {
+        self.lib_value(PUBLIC_SKIP_EXPORT_GLYPHS_KEY).and_then(string_array)
+    }
+
+    /// Set or remove `public.skipExportGlyphs`.
+    pub fn set_skip_export_glyphs(&mut self, glyphs: Option<Vec<String>>) {
+        self.set_lib_value(PUBLIC_SKIP_EXPORT_GLYPHS_KEY, glyphs.map(string_array_value));
+    }
+
+    /// Returns the glyphs used for Unicode variation sequences, from
+    /// `public.unicodeVariationSequences`.
+    ///
+    /// The outer map is keyed by variation selector, and the inner map
+    /// by the base character of the sequence.
+    pub fn unicode_variation_sequences(&self) -> Option<BTreeMap<char, BTreeMap<char, String>>> {
+        let sequences = self.lib_value(PUBLIC_UNICODE_VARIATION_SEQUENCES_KEY)?;
+        sequences
+            .as_dictionary()?
+            .iter()
+            .map(|(selector, bases)| {
+                let bases = string_dict(bases)?
+                    .into_iter()
+                    .map(|(base, glyph)| Some((parse_hex_char(&base)?, glyph)))
+                    .collect::<Option<_>>()?;
+                Some((parse_hex_char(selector)?, bases))
+            })
+            .collect()
+    }
+
+    /// Set or remove `public.unicodeVariationSequences`.
+    pub fn set_unicode_variation_sequences(
+        &mut self,
+        sequences: Option<BTreeMap<char, BTreeMap<char, String>>>,
+    ) {
+        let value = sequences.map(|sequences| {
+            let dict = sequences
+                .into_iter()
+                .map(|(selector, bases)| {
+                    let bases = bases
+                        .into_iter()
+                        .map(|(base, glyph)| (format_hex_char(base), Value::String(glyph)))
+                        .collect();
+                    (format_hex_char(selector), Value::Dictionary(bases))
+                })
+                .collect();
+            Value::Dictionary(dict)
+        });
+        self.set_lib_value(PUBLIC_UNICODE_VARIATION_SEQUENCES_KEY, value);
+    }
+
+    fn lib_value(&self, key: &str) -> Option<&Value> {
+        self.lib.as_ref().and_then(|lib| lib.get(key))
+    }
+
+    /// Rename the glyph `old` to `new` in the lib array or dictionary at
+    /// `key`, leaving every other entry as it is.
+    fn rename_in_lib_value(&mut self, key: &str, old: &str, new: &str) {
+        match self.lib.as_mut().and_then(|lib| lib.get_mut(key)) {
+            Some(Value::Array(glyphs)) => {
+                for glyph in glyphs.iter_mut().filter(|g| g.as_string() == Some(old)) {
+                    *glyph = Value::String(new.into());
+                }
+            }
+            Some(Value::Dictionary(dict)) => {
+                if let Some(value) = dict.remove(old) {
+                    dict.insert(new.into(), value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn set_lib_value(&mut self, key: &str, value: Option<Value>) {
+        match value {
+            Some(value) => {
+                self.lib.get_or_insert_with(Plist::new).insert(key.to_string(), value);
+            }
+            None => {
+                if let Some(lib) = self.lib.as_mut() {
+                    lib.remove(key);
+                }
+            }
+        }
     }
 
     /// Returns the first layer matching a predicate. The predicate takes a
@@ -58,11 +791,244 @@ impl Ufo {
     pub fn iter(&self) -> impl Iterator<Item = &LayerInfo> {
         self.layers.iter()
     }
+
+    /// Returns the default layer, which is stored in the `glyphs` directory.
+    pub fn default_layer(&self) -> &Layer {
+        &self.layers[self.default_layer_index()].layer
+    }
+
+    /// Returns the default layer, which is stored in the `glyphs` directory.
+    pub fn default_layer_mut(&mut self) -> &mut Layer {
+        let index = self.default_layer_index();
+        &mut self.layers[index].layer
+    }
+
+    /// Returns the layer with this name, if it exists.
+    pub fn get_layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name).map(|l| &l.layer)
+    }
+
+    /// Returns the layer with this name, if it exists.
+    pub fn get_layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name == name).map(|l| &mut l.layer)
+    }
+
+    /// Add a new, empty layer with this name after the existing layers, and
+    /// return it.
+    ///
+    /// The layer's directory name is derived from its name using
+    /// [`user_name_to_file_name`]. Returns [`Error::DuplicateLayer`] if a
+    /// layer with this name already exists.
+    ///
+    /// [`user_name_to_file_name`]: fn.user_name_to_file_name.html
+    /// [`Error::DuplicateLayer`]: enum.Error.html#variant.DuplicateLayer
+    pub fn new_layer(&mut self, name: &str) -> Result<&mut Layer, Error> {
+        if self.layer_index(name).is_some() {
+            return Err(Error::DuplicateLayer(name.into()));
+        }
+        let path = self.dir_name_for_layer(name, None);
+        self.layers.push(LayerInfo { name: name.into(), path, layer: Layer::new() });
+        Ok(&mut self.layers.last_mut().unwrap().layer)
+    }
+
+    /// Give the layer named `old` the name `new`.
+    ///
+    /// Layers other than the default layer are moved to a directory derived
+    /// from their new name when the font is saved. All of the layer's glyphs
+    /// are loaded first, so that they don't depend on the old directory; if
+    /// any of them fail to load, the layer is not renamed and
+    /// [`Error::GlyphErrors`] is returned.
+    ///
+    /// [`Error::GlyphErrors`]: enum.Error.html#variant.GlyphErrors
+    pub fn rename_layer(&mut self, old: &str, new: &str) -> Result<(), Error> {
+        let index = self.layer_index(old).ok_or_else(|| Error::MissingLayer(old.into()))?;
+        if old == new {
+            return Ok(());
+        }
+        if self.layer_index(new).is_some() {
+            return Err(Error::DuplicateLayer(new.into()));
+        }
+
+        if self.layers[index].path != Path::new(DEFAULT_GLYPHS_DIRNAME) {
+            let errors = self.layers[index].layer.load_all_glyphs();
+            if !errors.is_empty() {
+                let errors = errors
+                    .into_iter()
+                    .map(|(glyph, error)| GlyphError { layer: old.into(), glyph, error })
+                    .collect();
+                return Err(Error::GlyphErrors(errors));
+            }
+            self.layers[index].path = self.dir_name_for_layer(new, Some(index));
+        }
+        self.layers[index].name = new.into();
+        Ok(())
+    }
+
+    /// Remove the layer with this name, along with all of its glyphs.
+    ///
+    /// The layer's directory is deleted when the font is saved. Returns
+    /// [`Error::RemoveDefaultLayer`] if this is the default layer.
+    ///
+    /// [`Error::RemoveDefaultLayer`]: enum.Error.html#variant.RemoveDefaultLayer
+    pub fn remove_layer(&mut self, name: &str) -> Result<(), Error> {
+        let index = self.layer_index(name).ok_or_else(|| Error::MissingLayer(name.into()))?;
+        if index == self.default_layer_index() {
+            return Err(Error::RemoveDefaultLayer);
+        }
+        self.layers.remove(index);
+        Ok(())
+    }
+
+    /// Move the layer with this name to position `index` in the layer order,
+    /// shifting the layers after it.
+    ///
+    /// Returns [`Error::LayerIndexOutOfRange`] if `index` is not less than the
+    /// number of layers.
+    ///
+    /// [`Error::LayerIndexOutOfRange`]: enum.Error.html#variant.LayerIndexOutOfRange
+    pub fn move_layer(&mut self, name: &str, index: usize) -> Result<(), Error> {
+        if index >= self.layers.len() {
+            return Err(Error::LayerIndexOutOfRange(index));
+        }
+        let current = self.layer_index(name).ok_or_else(|| Error::MissingLayer(name.into()))?;
+        let layer = self.layers.remove(current);
+        self.layers.insert(index, layer);
+        Ok(())
+    }
+
+    fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.name == name)
+    }
+
+    fn default_layer_index(&self) -> usize {
+        self.layers
+            .iter()
+            .position(|l| l.path == Path::new(DEFAULT_GLYPHS_DIRNAME))
+            .expect("fonts always have a default layer")
+    }
+
+    /// Returns a directory name for a layer called `name` that no other layer
+    /// uses. The layer at index `exclude`, if any, is being renamed, so its
+    /// current directory name may be reused.
+    fn dir_name_for_layer(&self, name: &str, exclude: Option<usize>) -> PathBuf {
+        let existing = |candidate: &str| {
+            self.layers
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| Some(*i) != exclude)
+                .any(|(_, l)| l.path.to_string_lossy().to_lowercase() == candidate)
+        };
+        user_name_to_file_name(name, LAYER_DIRNAME_PREFIX, "", existing).into()
+    }
+}
+
+impl Default for Ufo {
+    fn default() -> Self {
+        Ufo::new()
+    }
+}
+
+impl OpenTypeCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            OpenTypeCategory::Unassigned => "unassigned",
+            OpenTypeCategory::Base => "base",
+            OpenTypeCategory::Ligature => "ligature",
+            OpenTypeCategory::Mark => "mark",
+            OpenTypeCategory::Component => "component",
+        }
+    }
+}
+
+impl FromStr for OpenTypeCategory {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unassigned" => Ok(OpenTypeCategory::Unassigned),
+            "base" => Ok(OpenTypeCategory::Base),
+            "ligature" => Ok(OpenTypeCategory::Ligature),
+            "mark" => Ok(OpenTypeCategory::Mark),
+            "component" => Ok(OpenTypeCategory::Component),
+            other => Err(format!("unknown OpenType category '{}'", other)),
+        }
+    }
+}
+
+fn string_array(value: &Value) -> Option<Vec<String>> {
+    value.as_array()?.iter().map(|v| v.as_string().map(String::from)).collect()
+}
+
+fn string_array_value(strings: Vec<String>) -> Value {
+    Value::Array(strings.into_iter().map(Value::String).collect())
+}
+
+fn string_dict(value: &Value) -> Option<BTreeMap<String, String>> {
+    value.as_dictionary()?.iter().map(|(k, v)| Some((k.clone(), v.as_string()?.into()))).collect()
+}
+
+/// Parse a code point written as hexadecimal, such as `FE00`.
+fn parse_hex_char(s: &str) -> Option<char> {
+    u32::from_str_radix(s, 16).ok().and_then(std::char::from_u32)
+}
+
+fn format_hex_char(c: char) -> String {
+    format!("{:04X}", c as u32)
+}
+
+/// Returns `true` if `dir` is a single directory name with the prefix used
+/// for layers other than the default layer.
+///
+/// Paths from `layercontents.plist` are checked with this before anything is
+/// deleted, so that a bad entry can't point outside the font.
+fn is_layer_dir_name(dir: &Path) -> bool {
+    let mut components = dir.components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => name
+            .to_str()
+            .map(|name| {
+                name.len() > LAYER_DIRNAME_PREFIX.len() && name.starts_with(LAYER_DIRNAME_PREFIX)
+            })
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Deserialize the plist file at `path`, if it exists.
+fn load_optional_plist<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    if path.exists() {
+        Ok(Some(plist::from_file(path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Read the plist dictionary at `path`, if it exists.
+fn load_optional_dict(path: &Path) -> Result<Option<Plist>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    match Value::from_file(path)? {
+        Value::Dictionary(dict) => Ok(Some(dict)),
+        _other => Err(plist::Error::InvalidData.into()),
+    }
+}
+
+/// Write `value` to `path` as an XML plist, or remove the file at `path`
+/// if `value` is `None`.
+fn save_optional_plist<T: Serialize>(path: &Path, value: Option<&T>) -> Result<(), Error> {
+    match value {
+        Some(value) => write::write_plist(path, value)?,
+        None if path.exists() => fs::remove_file(path)?,
+        None => (),
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::glyph::{GlifVersion, CONVERTED_FROM_V1_KEY};
+    use crate::Glyph;
 
     #[test]
     fn loading() {
@@ -73,4 +1039,566 @@ mod tests {
             .find_layer(|l| l.path.to_str() == Some("glyphs.background"))
             .expect("missing layer");
     }
+
+    #[test]
+    fn metainfo() {
+        let font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        assert_eq!(font_obj.meta.format_version, FormatVersion::V3);
+        assert_eq!(font_obj.meta.creator.as_deref(), Some("org.robofab.ufoLib"));
+
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        match Ufo::load(dir.path()) {
+            Err(Error::MissingFile(name)) => assert_eq!(name, "metainfo.plist"),
+            other => panic!("expected missing file, found {:?}", other.err()),
+        }
+
+        let metainfo = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<plist version=\"1.0\">
+<dict>
+\t<key>formatVersion</key>
+\t<integer>4</integer>
+</dict>
+</plist>";
+        fs::write(dir.path().join("metainfo.plist"), metainfo).unwrap();
+        match Ufo::load(dir.path()) {
+            Err(Error::UnsupportedFormatVersion(4)) => (),
+            other => panic!("expected unsupported version, found {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn load_ufo2() {
+        let mut font_obj = Ufo::load("testdata/ufo2test.ufo").unwrap();
+        assert_eq!(font_obj.meta.format_version, FormatVersion::V2);
+        assert_eq!(font_obj.iter().count(), 1);
+        let layer = font_obj.find_layer(|l| l.name == "public.default").unwrap();
+        assert!(layer.get_glyph("O").is_ok());
+
+        let font_info = font_obj.font_info.as_ref().unwrap();
+        assert_eq!(font_info.open_type_hhea_ascender, Some(751));
+        assert_eq!(font_info.family_name.as_deref(), Some("Upconvert Test"));
+
+        assert_eq!(font_obj.kerning_value("O", "A"), Some(-20.));
+        assert_eq!(font_obj.kerning_value("D", "V"), Some(-10.));
+        let renames = font_obj.kerning_group_renames.as_ref().unwrap();
+        assert_eq!(renames.first.get("public.kern1.O").map(String::as_str), Some("@MMK_L_O"));
+        assert_eq!(renames.second.get("public.kern2.A").map(String::as_str), Some("@MMK_R_A"));
+
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        font_obj.save(dir.path()).unwrap();
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.meta.format_version, FormatVersion::V3);
+        assert_eq!(loaded.kerning, font_obj.kerning);
+        assert_eq!(loaded.kerning_group_renames, None);
+    }
+
+    #[test]
+    fn save_ufo2() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/ufo2test.ufo").unwrap();
+        font_obj.font_info.as_mut().unwrap().woff_major_version = Some(1);
+        let options = SaveOptions { format_version: FormatVersion::V2 };
+        let losses = font_obj.save_with_options(dir.path(), &options).unwrap();
+        assert_eq!(losses, vec![DataLoss::FontInfo("woffMajorVersion")]);
+
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.meta.format_version, FormatVersion::V2);
+        assert_eq!(loaded.groups, font_obj.groups);
+        assert_eq!(loaded.kerning, font_obj.kerning);
+        let groups: Groups = plist::from_file(dir.path().join("groups.plist")).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert!(groups.contains("@MMK_L_O"));
+
+        // saving a UFO3 in place as UFO2 leaves nothing UFO3-only behind
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/normalizedtest.ufo").unwrap();
+        font_obj.data.insert("com.example.note", b"note".to_vec()).unwrap();
+        font_obj.images.insert("period sketch.png", b"\x89PNG\r\n\x1a\n".to_vec()).unwrap();
+        font_obj.save(dir.path()).unwrap();
+        assert!(dir.path().join("images/period sketch.png").exists());
+        let font_obj = Ufo::load(dir.path()).unwrap();
+        font_obj.save_with_options(dir.path(), &options).unwrap();
+        let files = files_in_dir(dir.path());
+        assert!(files.iter().all(|f| f.starts_with("glyphs/") || f.components().count() == 1));
+        assert!(!files.contains(&PathBuf::from("layercontents.plist")));
+        assert!(!files.contains(&PathBuf::from("glyphs/layerinfo.plist")));
+        assert_eq!(Ufo::load(dir.path()).unwrap().meta.format_version, FormatVersion::V2);
+
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        let losses = font_obj.save_with_options(dir.path(), &options).unwrap();
+        assert!(losses.contains(&DataLoss::Layer("public.background".into())));
+        assert!(!dir.path().join("layercontents.plist").exists());
+        assert!(!dir.path().join("glyphs.background").exists());
+        let glif = fs::read_to_string(dir.path().join("glyphs/A_.glif")).unwrap();
+        assert!(glif.contains("format=\"1\""));
+    }
+
+    #[test]
+    fn load_ufo1() {
+        let font_obj = Ufo::load("testdata/ufo1test.ufo").unwrap();
+        assert_eq!(font_obj.meta.format_version, FormatVersion::V1);
+        let font_info = font_obj.font_info.as_ref().unwrap();
+        assert_eq!(font_info.style_map_family_name.as_deref(), Some("Upconvert Test Bold"));
+        assert_eq!(font_info.open_type_os2_width_class, Some(5));
+        assert_eq!(font_info.postscript_font_name.as_deref(), Some("UpconvertTest-Bold"));
+    }
+
+    #[test]
+    fn save() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let path = "testdata/mutatorSans/MutatorSansLightWide.ufo";
+        let mut font_obj = Ufo::load(path).unwrap();
+        let glyph =
+            font_obj.find_layer(|l| l.path.to_str() == Some("glyphs")).unwrap().get_glyph("A");
+        let glyph = glyph.unwrap().clone();
+        font_obj.save(dir.path()).unwrap();
+
+        assert!(dir.path().join("metainfo.plist").exists());
+        assert!(dir.path().join("glyphs.background/contents.plist").exists());
+        let mut loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.iter().count(), 2);
+        let layer = loaded.find_layer(|l| l.path.to_str() == Some("glyphs")).unwrap();
+        assert_eq!(layer.get_glyph("A").unwrap(), &glyph);
+    }
+
+    #[test]
+    fn fontinfo_round_trip() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let font_obj = Ufo::load("testdata/fontinfotest.ufo").unwrap();
+        let font_info = font_obj.font_info.clone().expect("missing fontinfo");
+        assert_eq!(font_info.x_height, Some(500.into()));
+        font_obj.save(dir.path()).unwrap();
+
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.font_info, Some(font_info));
+    }
+
+    #[test]
+    fn groups_round_trip() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        let groups = font_obj.groups.clone().expect("missing groups");
+        font_obj.save(dir.path()).unwrap();
+        assert_eq!(Ufo::load(dir.path()).unwrap().groups, Some(groups));
+
+        let groups = font_obj.groups.as_mut().unwrap();
+        groups.get_mut("public.kern1.T").unwrap().push("O".into());
+        assert!(font_obj.save(dir.path()).is_err());
+    }
+
+    #[test]
+    fn kerning() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        assert_eq!(font_obj.kerning_value("Q", "Aacute"), Some(-20.));
+        assert_eq!(font_obj.kerning_value("C", "Q"), None);
+
+        font_obj.kerning.as_mut().unwrap().insert("C", "Q", 5.);
+        font_obj.save(dir.path()).unwrap();
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.kerning, font_obj.kerning);
+        assert_eq!(loaded.kerning_value("C", "Q"), Some(5.));
+
+        font_obj.kerning = None;
+        font_obj.save(dir.path()).unwrap();
+        assert!(!dir.path().join("kerning.plist").exists());
+    }
+
+    #[test]
+    fn features_round_trip() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        assert_eq!(font_obj.features, None);
+
+        let features = "languagesystem DFLT dflt;\ninclude(kern.fea);\n";
+        font_obj.features = Some(features.into());
+        font_obj.save(dir.path()).unwrap();
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.features.as_deref(), Some(features));
+    }
+
+    #[test]
+    fn data() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/datatest.ufo").unwrap();
+        assert_eq!(font_obj.data.len(), 2);
+        font_obj.data.insert("com.example.new", b"new".to_vec()).unwrap();
+        font_obj.save(dir.path()).unwrap();
+
+        let mut loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.data.len(), 3);
+        let config = loaded.data.get("com.example.tool/config.txt").unwrap().unwrap();
+        assert_eq!(config, b"spacing = tight\n");
+
+        let options = SaveOptions { format_version: FormatVersion::V2 };
+        let losses = loaded.save_with_options(dir.path().join("v2"), &options).unwrap();
+        assert_eq!(losses, vec![DataLoss::Data]);
+        assert!(!dir.path().join("v2/data").exists());
+    }
+
+    #[test]
+    fn images() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/imagetest.ufo").unwrap();
+        assert_eq!(font_obj.images.len(), 2);
+        let missing = font_obj.missing_images().unwrap();
+        let expected = MissingImage {
+            layer: "public.default".into(),
+            glyph: "b".into(),
+            file_name: "missing.png".into(),
+        };
+        assert_eq!(missing, vec![expected]);
+
+        font_obj.images.insert("missing.png", b"\x89PNG\r\n\x1a\n".to_vec()).unwrap();
+        assert!(font_obj.missing_images().unwrap().is_empty());
+        font_obj.save(dir.path()).unwrap();
+
+        // the text file is never referenced, so it is not written
+        let mut loaded = Ufo::load(dir.path()).unwrap();
+        let names: Vec<_> = loaded.images.names().map(Path::to_path_buf).collect();
+        assert_eq!(names, vec![PathBuf::from("missing.png"), PathBuf::from("sketch.png")]);
+        assert!(loaded.images.get("sketch.png").unwrap().is_ok());
+
+        loaded.find_layer(|l| l.name == "public.default").unwrap().delete_glyph("b");
+        loaded.save(dir.path()).unwrap();
+        assert!(!dir.path().join("images/missing.png").exists());
+        assert!(dir.path().join("images/sketch.png").exists());
+
+        let options = SaveOptions { format_version: FormatVersion::V2 };
+        let losses = loaded.save_with_options(dir.path().join("v2"), &options).unwrap();
+        assert!(losses.contains(&DataLoss::Images));
+        assert!(!dir.path().join("v2/images").exists());
+    }
+
+    #[test]
+    fn layers() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        assert!(font_obj.default_layer().contains_glyph("A"));
+        assert!(font_obj.get_layer("public.background").is_some());
+        assert!(font_obj.get_layer("glyphs").is_none());
+        font_obj.save(dir.path()).unwrap();
+
+        let mut font_obj = Ufo::load(dir.path()).unwrap();
+        font_obj.new_layer("Sketches").unwrap().set_glyph(Glyph::new_named("a"));
+        assert!(matches!(font_obj.new_layer("Sketches"), Err(Error::DuplicateLayer(_))));
+        font_obj.rename_layer("public.background", "Background").unwrap();
+        assert!(matches!(
+            font_obj.rename_layer("Background", "Sketches"),
+            Err(Error::DuplicateLayer(_))
+        ));
+        font_obj.rename_layer("public.default", "Foreground").unwrap();
+        font_obj.move_layer("Sketches", 0).unwrap();
+        assert!(matches!(font_obj.remove_layer("Foreground"), Err(Error::RemoveDefaultLayer)));
+        assert!(matches!(font_obj.remove_layer("public.background"), Err(Error::MissingLayer(_))));
+        font_obj.save(dir.path()).unwrap();
+
+        let mut font_obj = Ufo::load(dir.path()).unwrap();
+        let layers: Vec<_> = font_obj.iter().map(|l| (l.name.as_str(), l.path.clone())).collect();
+        let expected = vec![
+            ("Sketches", PathBuf::from("glyphs.S_ketches")),
+            ("Foreground", PathBuf::from("glyphs")),
+            ("Background", PathBuf::from("glyphs.B_ackground")),
+        ];
+        assert_eq!(layers, expected);
+        assert!(!dir.path().join("glyphs.background").exists());
+        assert!(font_obj.get_layer("Background").unwrap().contains_glyph("A"));
+
+        font_obj.remove_layer("Background").unwrap();
+        font_obj.save(dir.path()).unwrap();
+        assert!(!dir.path().join("glyphs.B_ackground").exists());
+        assert_eq!(Ufo::load(dir.path()).unwrap().iter().count(), 2);
+
+        // a renamed layer may reuse its own directory name
+        font_obj.new_layer("a_").unwrap();
+        font_obj.rename_layer("a_", "A").unwrap();
+        let layer = font_obj.iter().find(|l| l.name == "A").unwrap();
+        assert_eq!(layer.path, PathBuf::from("glyphs.A_"));
+        assert!(matches!(font_obj.move_layer("A", 3), Err(Error::LayerIndexOutOfRange(3))));
+    }
+
+    #[test]
+    fn failed_save_leaves_font_intact() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        Ufo::load("testdata/kerningtest.ufo").unwrap().save(dir.path()).unwrap();
+        let metainfo = fs::read_to_string(dir.path().join(METAINFO_FILE)).unwrap();
+        fs::write(dir.path().join("glyphs/A_.glif"), "<glyph>").unwrap();
+
+        let mut font_obj = Ufo::load(dir.path()).unwrap();
+        font_obj.rename_layer("public.background", "Background").unwrap();
+        assert!(font_obj.default_layer_mut().get_glyph("A").is_err());
+        font_obj.groups = None;
+        assert!(matches!(font_obj.save(dir.path()), Err(Error::SavedError(_))));
+
+        assert!(dir.path().join(GROUPS_FILE).exists());
+        assert_eq!(fs::read_to_string(dir.path().join(METAINFO_FILE)).unwrap(), metainfo);
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert!(loaded.get_layer("public.background").is_some());
+    }
+
+    #[test]
+    fn stale_layer_dirs_stay_inside_font() {
+        let dir = tempdir::TempDir::new("layers").unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        let font_path = dir.path().join("Test.ufo");
+        let font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        font_obj.save(&font_path).unwrap();
+
+        let old_contents = vec![
+            ("public.default", PathBuf::from("glyphs")),
+            ("absolute", outside.clone()),
+            ("relative", PathBuf::from("../outside")),
+            ("default", PathBuf::from("glyphs")),
+        ];
+        write::write_plist(&font_path.join(LAYER_CONTENTS_FILE), &old_contents).unwrap();
+        font_obj.save(&font_path).unwrap();
+        assert!(outside.exists());
+        assert!(font_path.join("glyphs.background").exists());
+    }
+
+    #[test]
+    fn new_font() {
+        let dir = tempdir::TempDir::new("New.ufo").unwrap();
+        let mut font_obj = Ufo::new();
+        assert_eq!(font_obj.meta.creator.as_deref(), Some(DEFAULT_METAINFO_CREATOR));
+        assert_eq!(font_obj.iter().next().unwrap().name, DEFAULT_LAYER_NAME);
+        font_obj.default_layer_mut().set_glyph(Glyph::new_named("A"));
+        font_obj.save(dir.path()).unwrap();
+
+        let files = files_in_dir(dir.path());
+        let expected: Vec<PathBuf> = vec![
+            "fontinfo.plist".into(),
+            "glyphs/A_.glif".into(),
+            "glyphs/contents.plist".into(),
+            "layercontents.plist".into(),
+            "metainfo.plist".into(),
+        ];
+        assert_eq!(files, expected);
+
+        let mut loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.meta, font_obj.meta);
+        assert_eq!(loaded.font_info, Some(FontInfo::default()));
+        assert!(loaded.default_layer_mut().get_glyph("A").is_ok());
+    }
+
+    #[test]
+    fn rename_glyph() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/normalizedtest.ufo").unwrap();
+        assert!(matches!(font_obj.rename_glyph("B", "C"), Err(Error::MissingGlyph)));
+        assert!(matches!(font_obj.rename_glyph("A", "space"), Err(Error::DuplicateGlyph(_))));
+
+        // glyphs that are only named in the groups or kerning can't be merged
+        let mut merged = Ufo::load("testdata/normalizedtest.ufo").unwrap();
+        merged.groups.as_mut().unwrap().insert("round", vec!["o".into()]);
+        merged.kerning.as_mut().unwrap().insert("space", "v", 5.);
+        for name in ["o", "v"].iter() {
+            match merged.rename_glyph("period", name) {
+                Err(Error::DuplicateGlyph(dupe)) => assert_eq!(&dupe, name),
+                other => panic!("expected duplicate glyph, found {:?}", other),
+            }
+        }
+
+        let categories = vec![
+            ("A".to_string(), Value::String("nonsense".into())),
+            ("Aacute".to_string(), Value::String("base".into())),
+        ];
+        let categories = Value::Dictionary(categories.into_iter().collect());
+        font_obj.set_lib_value(PUBLIC_OPENTYPE_CATEGORIES_KEY, Some(categories));
+        let mut sequences = BTreeMap::new();
+        let bases = vec![('A', "A".to_string()), ('B', "Aacute".to_string())];
+        sequences.insert('\u{FE00}', bases.into_iter().collect());
+        font_obj.set_unicode_variation_sequences(Some(sequences));
+        font_obj.rename_glyph("A", "Alpha").unwrap();
+        font_obj.rename_glyph("Aacute", "Alphatonos").unwrap();
+        font_obj.save(dir.path()).unwrap();
+        assert!(!dir.path().join("glyphs/A_.glif").exists());
+        assert!(dir.path().join("glyphs/A_lpha.glif").exists());
+        assert!(dir.path().join("glyphs.public.background/A_lpha.glif").exists());
+
+        let mut loaded = Ufo::load(dir.path()).unwrap();
+        let glyph = loaded.default_layer_mut().get_glyph("Alphatonos").unwrap();
+        assert_eq!(glyph.name, "Alphatonos");
+        assert_eq!(glyph.outline.as_ref().unwrap().components().next().unwrap().base, "Alpha");
+        let background = loaded.get_layer_mut("public.background").unwrap();
+        assert_eq!(background.get_glyph("Alpha").unwrap().name, "Alpha");
+        assert!(!background.contains_glyph("A"));
+
+        let groups = loaded.groups.as_ref().unwrap();
+        assert_eq!(groups.get("public.kern1.A").unwrap(), &["Alpha", "Alphatonos"]);
+        assert_eq!(loaded.kerning.as_ref().unwrap().get("space", "Alpha"), Some(-10.));
+        assert_eq!(loaded.kerning.as_ref().unwrap().get("space", "A"), None);
+        assert_eq!(loaded.glyph_order().unwrap(), ["space", "Alpha", "Alphatonos", "period"]);
+        let postscript_names = loaded.postscript_names().unwrap();
+        assert_eq!(postscript_names.get("Alphatonos").map(String::as_str), Some("uni00C1"));
+        assert!(!postscript_names.contains_key("Aacute"));
+        let categories = loaded.lib_value(PUBLIC_OPENTYPE_CATEGORIES_KEY).unwrap();
+        let categories = categories.as_dictionary().unwrap();
+        let names: Vec<_> = categories.keys().map(String::as_str).collect();
+        assert_eq!(names, ["Alpha", "Alphatonos"]);
+        assert_eq!(categories["Alpha"], Value::String("nonsense".into()));
+        let sequences = loaded.unicode_variation_sequences().unwrap();
+        let bases = &sequences[&'\u{FE00}'];
+        assert_eq!(bases[&'A'], "Alpha");
+        assert_eq!(bases[&'B'], "Alphatonos");
+    }
+
+    #[test]
+    fn save_ufo2_glyphs_as_ufo3() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/ufo2test.ufo").unwrap();
+        font_obj.default_layer_mut().get_glyph("A").unwrap();
+        font_obj.save(dir.path()).unwrap();
+
+        for name in ["A_.glif", "O_.glif"].iter() {
+            let glyph = Glyph::load_raw(dir.path().join("glyphs").join(name)).unwrap();
+            assert_eq!(glyph.format, GlifVersion::V2);
+            assert!(glyph.lib.map(|lib| !lib.contains_key(CONVERTED_FROM_V1_KEY)).unwrap_or(true));
+        }
+        let glyph = Glyph::load_raw(dir.path().join("glyphs/O_.glif")).unwrap();
+        assert_eq!(glyph.anchors.as_ref().map(Vec::len), Some(1));
+
+        // saving a UFO2 in place as UFO3 converts the glyphs left on disk
+        let v2_path = dir.path().join("v2");
+        let options = SaveOptions { format_version: FormatVersion::V2 };
+        Ufo::load("testdata/ufo2test.ufo").unwrap().save_with_options(&v2_path, &options).unwrap();
+        Ufo::load(&v2_path).unwrap().save(&v2_path).unwrap();
+        let glyph = Glyph::load_raw(v2_path.join("glyphs/O_.glif")).unwrap();
+        assert_eq!(glyph.format, GlifVersion::V2);
+        assert_eq!(glyph.anchors.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn keep_raw_glyphs() {
+        let mut font_obj = Ufo::load("testdata/ufo2test.ufo").unwrap();
+        let glyph = font_obj.default_layer_mut().get_glyph("O").unwrap();
+        assert_eq!(glyph.format, GlifVersion::V2);
+        let lib = glyph.lib.as_ref().unwrap();
+        assert_eq!(lib.get(CONVERTED_FROM_V1_KEY), Some(&Value::Boolean(true)));
+
+        let options = LoadOptions { keep_raw_glyphs: true, ..Default::default() };
+        let mut font_obj = Ufo::load_with("testdata/ufo2test.ufo", &options).unwrap();
+        let glyph = font_obj.default_layer_mut().get_glyph("O").unwrap();
+        assert_eq!(glyph.format, GlifVersion::V1);
+        assert_eq!(glyph.anchors, None);
+
+        // raw glyphs are still written as format 2 in a UFO3
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        font_obj.save(dir.path()).unwrap();
+        let glyph = Glyph::load_raw(dir.path().join("glyphs/O_.glif")).unwrap();
+        assert_eq!(glyph.format, GlifVersion::V2);
+        assert_eq!(glyph.anchors.as_ref().map(Vec::len), Some(1));
+
+        let options = SaveOptions { format_version: FormatVersion::V2 };
+        font_obj.save_with_options(dir.path(), &options).unwrap();
+        let glyph = Glyph::load_raw(dir.path().join("glyphs/O_.glif")).unwrap();
+        assert_eq!(glyph.format, GlifVersion::V1);
+        assert!(glyph.lib.map(|lib| !lib.contains_key(CONVERTED_FROM_V1_KEY)).unwrap_or(true));
+    }
+
+    #[test]
+    fn load_glyphs_eagerly() {
+        let options = LoadOptions { load_glyphs: true, ..Default::default() };
+        let mut font_obj = Ufo::load_with("testdata/normalizedtest.ufo", &options).unwrap();
+        let layer = font_obj.find_layer(|layer| layer.name == "public.background").unwrap();
+        assert!(layer.get_glyph("A").is_ok());
+
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        font_obj.save(dir.path()).unwrap();
+        fs::write(dir.path().join("glyphs/A_.glif"), "<glyph>").unwrap();
+        fs::write(dir.path().join("glyphs.public.background/A_.glif"), "").unwrap();
+        match Ufo::load_with(dir.path(), &options) {
+            Err(Error::GlyphErrors(errors)) => {
+                let failed: Vec<_> =
+                    errors.iter().map(|e| (e.layer.as_str(), e.glyph.as_str())).collect();
+                assert_eq!(failed, vec![("public.default", "A"), ("public.background", "A")]);
+            }
+            other => panic!("expected glyph errors, found {:?}", other.err()),
+        }
+        assert!(Ufo::load(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn normalized_round_trip() {
+        let source = Path::new("testdata/normalizedtest.ufo");
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load(source).unwrap();
+        // make sure every glyph is serialized, rather than copied
+        for layer in font_obj.layers.iter_mut() {
+            let names: Vec<String> = layer.layer.glyph_names().map(String::from).collect();
+            for name in names {
+                layer.layer.get_glyph(&name).unwrap();
+            }
+        }
+        font_obj.save(dir.path()).unwrap();
+
+        let expected = files_in_dir(source);
+        assert_eq!(files_in_dir(dir.path()), expected);
+        for file in expected {
+            let original = fs::read_to_string(source.join(&file)).unwrap();
+            let written = fs::read_to_string(dir.path().join(&file)).unwrap();
+            assert_eq!(original, written, "{} differs", file.display());
+        }
+    }
+
+    /// Returns the paths of all files in `dir` and its subdirectories,
+    /// relative to `dir`.
+    fn files_in_dir(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(next) = dirs.pop() {
+            for entry in fs::read_dir(next).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    files.push(path.strip_prefix(dir).unwrap().to_path_buf());
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn lib() {
+        let dir = tempdir::TempDir::new("Test.ufo").unwrap();
+        let mut font_obj = Ufo::load("testdata/kerningtest.ufo").unwrap();
+        let lib = font_obj.lib.clone().expect("missing lib");
+        assert_eq!(lib.get("com.example.custom").and_then(Value::as_integer), Some(42));
+
+        let order = font_obj.glyph_order().unwrap();
+        assert_eq!(order, vec!["A", "Aacute", "C", "D", "O", "Q", "T", "V"]);
+        let names = font_obj.postscript_names().unwrap();
+        assert_eq!(names.get("Aacute").map(String::as_str), Some("uni00C1"));
+        let categories = font_obj.open_type_categories().unwrap();
+        assert_eq!(categories.get("A"), Some(&OpenTypeCategory::Base));
+        assert_eq!(categories.get("Aacute"), Some(&OpenTypeCategory::Ligature));
+        assert_eq!(font_obj.skip_export_glyphs(), Some(vec![]));
+        let sequences = font_obj.unicode_variation_sequences().unwrap();
+        assert_eq!(sequences[&'\u{FE00}'][&'A'], "A");
+
+        font_obj.save(dir.path()).unwrap();
+        assert_eq!(Ufo::load(dir.path()).unwrap().lib, Some(lib));
+
+        font_obj.set_glyph_order(Some(vec!["V".into(), "A".into()]));
+        font_obj.set_postscript_names(None);
+        font_obj.set_open_type_categories(Some(categories));
+        font_obj.set_skip_export_glyphs(Some(vec!["Q".into()]));
+        font_obj.set_unicode_variation_sequences(Some(sequences.clone()));
+        font_obj.save(dir.path()).unwrap();
+
+        let loaded = Ufo::load(dir.path()).unwrap();
+        assert_eq!(loaded.glyph_order(), Some(vec!["V".into(), "A".into()]));
+        assert_eq!(loaded.postscript_names(), None);
+        assert_eq!(loaded.skip_export_glyphs(), Some(vec!["Q".into()]));
+        assert_eq!(loaded.unicode_variation_sequences(), Some(sequences));
+        assert_eq!(loaded.lib, font_obj.lib);
+
+        font_obj.lib = Some(Plist::new());
+        font_obj.save(dir.path()).unwrap();
+        assert_eq!(Ufo::load(dir.path()).unwrap().lib, Some(Plist::new()));
+    }
 }
@@ -0,0 +1,343 @@
+//! Converting data from UFO1 and UFO2 fonts to the UFO3 model.
+
+use std::collections::BTreeMap;
+
+use plist::Value;
+use serde::Deserialize;
+
+use crate::fontinfo::FontInfo;
+use crate::glyph::Plist;
+use crate::groups::{Groups, KERN1_PREFIX, KERN2_PREFIX};
+use crate::kerning::Kerning;
+use crate::ufo::FormatVersion;
+use crate::Error;
+
+/// The prefix used by some UFO2 editors for groups on the first side of a
+/// kerning pair.
+static UFO2_KERN1_PREFIX: &str = "@MMK_L_";
+/// The prefix used by some UFO2 editors for groups on the second side of a
+/// kerning pair.
+static UFO2_KERN2_PREFIX: &str = "@MMK_R_";
+
+/// `fontinfo.plist` keys that were renamed in UFO2, and their new names.
+static FONTINFO_RENAMES_V1: &[(&str, &str)] = &[
+    ("menuName", "styleMapFamilyName"),
+    ("designer", "openTypeNameDesigner"),
+    ("designerURL", "openTypeNameDesignerURL"),
+    ("createdBy", "openTypeNameManufacturer"),
+    ("vendorURL", "openTypeNameManufacturerURL"),
+    ("license", "openTypeNameLicense"),
+    ("licenseURL", "openTypeNameLicenseURL"),
+    ("ttVersion", "openTypeNameVersion"),
+    ("ttUniqueID", "openTypeNameUniqueID"),
+    ("notice", "openTypeNameDescription"),
+    ("otFamilyName", "openTypeNamePreferredFamilyName"),
+    ("otStyleName", "openTypeNamePreferredSubfamilyName"),
+    ("otMacName", "openTypeNameCompatibleFullName"),
+    ("msCharSet", "postscriptWindowsCharacterSet"),
+    ("fondID", "macintoshFONDFamilyID"),
+    ("fondName", "macintoshFONDName"),
+    ("fontStyle", "styleMapStyleName"),
+    ("weightValue", "openTypeOS2WeightClass"),
+    ("widthName", "openTypeOS2WidthClass"),
+    ("ttVendor", "openTypeOS2VendorID"),
+    ("fontName", "postscriptFontName"),
+    ("fullName", "postscriptFullName"),
+    ("slantAngle", "postscriptSlantAngle"),
+    ("uniqueID", "postscriptUniqueID"),
+    ("weightName", "postscriptWeightName"),
+    ("defaultWidth", "postscriptDefaultWidthX"),
+];
+
+/// `fontinfo.plist` keys that may be floats in UFO2 but must be integers in UFO3.
+static FONTINFO_INTEGER_KEYS_V3: &[&str] = &[
+    "versionMajor",
+    "versionMinor",
+    "openTypeHeadLowestRecPPEM",
+    "openTypeHheaAscender",
+    "openTypeHheaDescender",
+    "openTypeHheaLineGap",
+    "openTypeHheaCaretSlopeRise",
+    "openTypeHheaCaretSlopeRun",
+    "openTypeHheaCaretOffset",
+    "openTypeOS2WidthClass",
+    "openTypeOS2WeightClass",
+    "openTypeOS2TypoAscender",
+    "openTypeOS2TypoDescender",
+    "openTypeOS2TypoLineGap",
+    "openTypeOS2WinAscent",
+    "openTypeOS2WinDescent",
+    "openTypeOS2SubscriptXSize",
+    "openTypeOS2SubscriptYSize",
+    "openTypeOS2SubscriptXOffset",
+    "openTypeOS2SubscriptYOffset",
+    "openTypeOS2SuperscriptXSize",
+    "openTypeOS2SuperscriptYSize",
+    "openTypeOS2SuperscriptXOffset",
+    "openTypeOS2SuperscriptYOffset",
+    "openTypeOS2StrikeoutSize",
+    "openTypeOS2StrikeoutPosition",
+    "openTypeVheaVertTypoAscender",
+    "openTypeVheaVertTypoDescender",
+    "openTypeVheaVertTypoLineGap",
+    "openTypeVheaCaretSlopeRise",
+    "openTypeVheaCaretSlopeRun",
+    "openTypeVheaCaretOffset",
+    "postscriptUniqueID",
+    "postscriptWindowsCharacterSet",
+    "macintoshFONDFamilyID",
+];
+
+/// The kerning groups that were renamed when a font was converted to UFO3.
+///
+/// Each map is keyed by the new `public.kern1.` or `public.kern2.` name,
+/// and the value is the original name of the group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KerningGroupRenames {
+    pub first: BTreeMap<String, String>,
+    pub second: BTreeMap<String, String>,
+}
+
+impl KerningGroupRenames {
+    /// Returns `true` if no groups were renamed.
+    pub fn is_empty(&self) -> bool {
+        self.first.is_empty() && self.second.is_empty()
+    }
+}
+
+/// Convert the contents of a UFO1 or UFO2 `fontinfo.plist` to a UFO3 `FontInfo`.
+pub(crate) fn upconvert_font_info(
+    mut info: Plist,
+    version: FormatVersion,
+) -> Result<FontInfo, Error> {
+    if version == FormatVersion::V1 {
+        info = font_info_v1_to_v2(info);
+    }
+
+    for key in FONTINFO_INTEGER_KEYS_V3 {
+        if let Some(Value::Real(value)) = info.get(*key) {
+            let value = Value::Integer(value.round() as i64);
+            info.insert(key.to_string(), value);
+        }
+    }
+
+    let events = Value::Dictionary(info).into_events().into_iter().map(Ok);
+    let font_info = FontInfo::deserialize(&mut plist::Deserializer::new(events))?;
+    Ok(font_info)
+}
+
+/// Rename UFO1 `fontinfo.plist` keys and convert their values to UFO2 form.
+fn font_info_v1_to_v2(info: Plist) -> Plist {
+    let mut result = Plist::new();
+    for (key, value) in info {
+        let new_key = FONTINFO_RENAMES_V1
+            .iter()
+            .find(|(old, _)| *old == key)
+            .map(|(_, new)| new.to_string())
+            .unwrap_or(key);
+
+        let value = match new_key.as_str() {
+            "styleMapStyleName" => value.as_integer().and_then(style_map_style_name_v1),
+            "openTypeOS2WidthClass" => value.as_string().and_then(width_class_v1),
+            "postscriptWindowsCharacterSet" => value.as_integer().and_then(character_set_v1),
+            _ => Some(value),
+        };
+        if let Some(value) = value {
+            result.insert(new_key, value);
+        }
+    }
+    result
+}
+
+fn style_map_style_name_v1(font_style: i64) -> Option<Value> {
+    let name = match font_style {
+        64 => "regular",
+        1 => "italic",
+        32 => "bold",
+        33 => "bold italic",
+        _ => return None,
+    };
+    Some(Value::String(name.into()))
+}
+
+fn width_class_v1(width_name: &str) -> Option<Value> {
+    let class = match width_name {
+        "Ultra-condensed" => 1,
+        "Extra-condensed" => 2,
+        "Condensed" => 3,
+        "Semi-condensed" => 4,
+        "Medium (normal)" => 5,
+        "Semi-expanded" => 6,
+        "Expanded" => 7,
+        "Extra-expanded" => 8,
+        "Ultra-expanded" => 9,
+        _ => return None,
+    };
+    Some(Value::Integer(class))
+}
+
+fn character_set_v1(ms_char_set: i64) -> Option<Value> {
+    let character_set = match ms_char_set {
+        0 => 1,
+        1 => 2,
+        2 => 3,
+        77 => 4,
+        128 => 5,
+        129 => 6,
+        130 => 7,
+        134 => 8,
+        136 => 9,
+        161 => 10,
+        162 => 11,
+        163 => 12,
+        177 => 13,
+        178 => 14,
+        186 => 15,
+        200 => 16,
+        204 => 17,
+        222 => 18,
+        238 => 19,
+        255 => 20,
+        _ => return None,
+    };
+    Some(Value::Integer(character_set))
+}
+
+/// Give the groups used in UFO1 or UFO2 kerning `public.kern1.` and
+/// `public.kern2.` names.
+///
+/// A group is a kerning group if it is used on one side of a kerning pair,
+/// or if its name starts with `@MMK_L_` or `@MMK_R_`. Names that are also
+/// glyph names, according to `is_glyph`, are treated as glyphs.
+///
+/// The original groups are kept, and a copy of each kerning group is added
+/// under its new name; pairs in `kerning` are updated to use the new names.
+pub(crate) fn upconvert_kerning(
+    kerning: &Kerning,
+    groups: &Groups,
+    is_glyph: impl Fn(&str) -> bool,
+) -> (Kerning, Groups, KerningGroupRenames) {
+    let is_group = |name: &str| groups.contains(name) && !is_glyph(name);
+
+    let mut first_groups = Vec::new();
+    let mut second_groups = Vec::new();
+    for (name, _) in groups.iter() {
+        if name.starts_with(UFO2_KERN1_PREFIX) {
+            first_groups.push(name.as_str());
+        } else if name.starts_with(UFO2_KERN2_PREFIX) {
+            second_groups.push(name.as_str());
+        }
+    }
+    for (first, second, _) in kerning.iter() {
+        if is_group(first) && !first.starts_with(KERN1_PREFIX) && !first_groups.contains(&first) {
+            first_groups.push(first);
+        }
+        if is_group(second) && !second.starts_with(KERN2_PREFIX) && !second_groups.contains(&second)
+        {
+            second_groups.push(second);
+        }
+    }
+    first_groups.sort();
+    second_groups.sort();
+
+    let mut new_groups = groups.clone();
+    let mut first_renames = BTreeMap::new();
+    for old in first_groups {
+        let name = format!("{}{}", KERN1_PREFIX, old.trim_start_matches(UFO2_KERN1_PREFIX));
+        let name = unique_group_name(name, &new_groups);
+        new_groups.insert(name.clone(), groups.get(old).cloned().unwrap_or_default());
+        first_renames.insert(old.to_string(), name);
+    }
+    let mut second_renames = BTreeMap::new();
+    for old in second_groups {
+        let name = format!("{}{}", KERN2_PREFIX, old.trim_start_matches(UFO2_KERN2_PREFIX));
+        let name = unique_group_name(name, &new_groups);
+        new_groups.insert(name.clone(), groups.get(old).cloned().unwrap_or_default());
+        second_renames.insert(old.to_string(), name);
+    }
+
+    let mut new_kerning = Kerning::new();
+    for (first, second, value) in kerning.iter() {
+        let first = first_renames.get(first).map(String::as_str).unwrap_or(first);
+        let second = second_renames.get(second).map(String::as_str).unwrap_or(second);
+        new_kerning.insert(first, second, value);
+    }
+
+    let invert = |renames: BTreeMap<String, String>| {
+        renames.into_iter().map(|(old, new)| (new, old)).collect()
+    };
+    let renames =
+        KerningGroupRenames { first: invert(first_renames), second: invert(second_renames) };
+    (new_kerning, new_groups, renames)
+}
+
+/// Append a number to `name`, if needed, so it doesn't clash with an existing group.
+fn unique_group_name(name: String, groups: &Groups) -> String {
+    if !groups.contains(&name) {
+        return name;
+    }
+    (1..).map(|i| format!("{}{}", name, i)).find(|n| !groups.contains(n)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fontinfo::StyleMapStyleName;
+
+    fn glyphs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn font_info_v1() {
+        let mut info = Plist::new();
+        info.insert("familyName".into(), Value::String("Rofls".into()));
+        info.insert("menuName".into(), Value::String("Rofls Bold".into()));
+        info.insert("fontStyle".into(), Value::Integer(33));
+        info.insert("widthName".into(), Value::String("Condensed".into()));
+        info.insert("msCharSet".into(), Value::Integer(0));
+        info.insert("ttVendor".into(), Value::String("NONE".into()));
+        info.insert("weightValue".into(), Value::Real(700.0));
+
+        let font_info = upconvert_font_info(info, FormatVersion::V1).unwrap();
+        assert_eq!(font_info.family_name.as_deref(), Some("Rofls"));
+        assert_eq!(font_info.style_map_family_name.as_deref(), Some("Rofls Bold"));
+        assert_eq!(font_info.style_map_style_name, Some(StyleMapStyleName::BoldItalic));
+        assert_eq!(font_info.open_type_os2_width_class, Some(3));
+        assert_eq!(font_info.postscript_windows_character_set, Some(1));
+        assert_eq!(font_info.open_type_os2_vendor_id.as_deref(), Some("NONE"));
+        assert_eq!(font_info.open_type_os2_weight_class, Some(700));
+    }
+
+    #[test]
+    fn kerning_groups() {
+        let mut groups = Groups::new();
+        groups.insert("@MMK_L_O", glyphs(&["O", "D"]));
+        groups.insert("@MMK_R_A", glyphs(&["A"]));
+        groups.insert("@MMK_L_unused", glyphs(&["V"]));
+        groups.insert("T", glyphs(&["T"]));
+        groups.insert("round", glyphs(&["O"]));
+        groups.insert("public.kern2.round", glyphs(&["C"]));
+
+        let mut kerning = Kerning::new();
+        kerning.insert("@MMK_L_O", "@MMK_R_A", -20.);
+        kerning.insert("@MMK_L_O", "A", -10.);
+        kerning.insert("T", "round", -30.);
+
+        let (kerning, groups, renames) = upconvert_kerning(&kerning, &groups, |name| name == "T");
+        assert_eq!(kerning.get("public.kern1.O", "public.kern2.A"), Some(-20.));
+        assert_eq!(kerning.get("public.kern1.O", "A"), Some(-10.));
+        assert_eq!(kerning.get("T", "public.kern2.round1"), Some(-30.));
+        assert_eq!(kerning.len(), 3);
+
+        assert_eq!(groups.get("public.kern1.O"), Some(&glyphs(&["O", "D"])));
+        assert_eq!(groups.get("public.kern1.unused"), Some(&glyphs(&["V"])));
+        assert_eq!(groups.get("public.kern2.round1"), Some(&glyphs(&["O"])));
+        assert!(groups.contains("@MMK_L_O"));
+
+        assert_eq!(renames.first.get("public.kern1.O").map(String::as_str), Some("@MMK_L_O"));
+        assert_eq!(renames.second.get("public.kern2.round1").map(String::as_str), Some("round"));
+        assert_eq!(renames.first.len(), 2);
+        assert_eq!(renames.second.len(), 2);
+    }
+}
@@ -0,0 +1,226 @@
+//! Writing XML in the format produced by [ufoNormalizer].
+//!
+//! Files are indented with tabs, dictionary keys are sorted, and numbers are
+//! written in their shortest form, without trailing zeros. A font that has
+//! been normalized is written back byte-for-byte unchanged.
+//!
+//! [ufoNormalizer]: https://github.com/unified-font-object/ufoNormalizer
+
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use plist::stream::Event as PlistEvent;
+use plist::Value;
+use quick_xml::{
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Error as XmlError, Writer,
+};
+use serde::Serialize;
+
+use crate::Error;
+
+static PLIST_DOCTYPE: &str = r#" plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd""#;
+
+/// Returns a new writer that indents with tabs, after writing the XML
+/// declaration.
+pub(crate) fn new_writer() -> Result<Writer<Cursor<Vec<u8>>>, XmlError> {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b'\t', 1);
+    writer.write_event(Event::Decl(BytesDecl::new(b"1.0", Some(b"UTF-8"), None)))?;
+    Ok(writer)
+}
+
+/// Returns the contents of `writer`, ending with a newline.
+pub(crate) fn finish(mut writer: Writer<Cursor<Vec<u8>>>) -> Result<Vec<u8>, XmlError> {
+    writer.write(b"\n")?;
+    Ok(writer.into_inner().into_inner())
+}
+
+/// Write `value` as a property list file at `path`.
+pub(crate) fn write_plist<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    let mut serializer = plist::Serializer::new(EventCollector(Vec::new()));
+    value.serialize(&mut serializer)?;
+    let events = serializer.into_inner().0;
+    let value = Value::from_events(events.into_iter().map(Ok))?;
+    write_plist_value(path, &value)
+}
+
+/// Write `value` as a property list file at `path`.
+pub(crate) fn write_plist_value(path: &Path, value: &Value) -> Result<(), Error> {
+    fs::write(path, plist_to_vec(value)?)?;
+    Ok(())
+}
+
+fn plist_to_vec(value: &Value) -> Result<Vec<u8>, XmlError> {
+    let mut writer = new_writer()?;
+    writer.write_event(Event::DocType(BytesText::from_escaped_str(PLIST_DOCTYPE)))?;
+    let mut start = BytesStart::borrowed_name(b"plist");
+    start.push_attribute(("version", "1.0"));
+    writer.write_event(Event::Start(start))?;
+    write_value(value, &mut writer)?;
+    writer.write_event(Event::End(BytesEnd::borrowed(b"plist")))?;
+    finish(writer)
+}
+
+/// A plist event sink, used to turn serde types into a `Value`.
+struct EventCollector(Vec<PlistEvent>);
+
+impl plist::stream::Writer for EventCollector {
+    fn write(&mut self, event: &PlistEvent) -> Result<(), plist::Error> {
+        self.0.push(event.clone());
+        Ok(())
+    }
+}
+
+/// Write a property list value as XML, at the writer's current indentation.
+///
+/// Reals without a fractional part are written as integers.
+pub(crate) fn write_value<T: Write>(value: &Value, writer: &mut Writer<T>) -> Result<(), XmlError> {
+    match value {
+        Value::Dictionary(dict) if dict.is_empty() => {
+            writer.write_event(Event::Empty(BytesStart::borrowed_name(b"dict")))?;
+        }
+        Value::Dictionary(dict) => {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"dict")))?;
+            for (key, value) in dict.iter() {
+                write_text_element(b"key", key, writer)?;
+                write_value(value, writer)?;
+            }
+            writer.write_event(Event::End(BytesEnd::borrowed(b"dict")))?;
+        }
+        Value::Array(array) if array.is_empty() => {
+            writer.write_event(Event::Empty(BytesStart::borrowed_name(b"array")))?;
+        }
+        Value::Array(array) => {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"array")))?;
+            for item in array {
+                write_value(item, writer)?;
+            }
+            writer.write_event(Event::End(BytesEnd::borrowed(b"array")))?;
+        }
+        Value::Boolean(true) => {
+            writer.write_event(Event::Empty(BytesStart::borrowed_name(b"true")))?;
+        }
+        Value::Boolean(false) => {
+            writer.write_event(Event::Empty(BytesStart::borrowed_name(b"false")))?;
+        }
+        Value::Data(data) => write_text_element(b"data", &base64::encode(data), writer)?,
+        Value::Date(date) => write_text_element(b"date", &format!("{:?}", date), writer)?,
+        Value::Real(real) => {
+            let text = format_number(*real);
+            let name: &[u8] = if text.contains('.') { b"real" } else { b"integer" };
+            write_text_element(name, &text, writer)?;
+        }
+        Value::Integer(int) => write_text_element(b"integer", &int.to_string(), writer)?,
+        Value::String(string) => write_text_element(b"string", string, writer)?,
+    }
+    Ok(())
+}
+
+/// Write `<name>text</name>` on a single line.
+pub(crate) fn write_text_element<T: Write>(
+    name: &[u8],
+    text: &str,
+    writer: &mut Writer<T>,
+) -> Result<(), XmlError> {
+    writer.write_event(Event::Start(BytesStart::borrowed_name(name)))?;
+    writer.write_event(Event::Text(BytesText::from_escaped_str(escape_text(text))))?;
+    writer.write_event(Event::End(BytesEnd::borrowed(name)))?;
+    Ok(())
+}
+
+/// Add an attribute to `start`, escaping only the characters that must be
+/// escaped.
+pub(crate) fn push_attribute(start: &mut BytesStart, key: &str, value: &str) {
+    let value = escape_attribute(value);
+    start.push_attribute((key.as_bytes(), value.as_bytes()));
+}
+
+/// Format a number the way ufoNormalizer does: with at most ten decimal
+/// places, without trailing zeros, and without a decimal point if nothing is
+/// left after it.
+///
+/// ufoNormalizer works with the 64-bit float parsed from the file, so the
+/// shortest representation of `value` is parsed as an `f64` before rounding;
+/// this keeps `0.1f32` from becoming "0.1000000015".
+pub(crate) fn format_number<T: Display>(value: T) -> String {
+    let value: f64 = value.to_string().parse().expect("floats display as valid f64");
+    let text = format!("{:.10}", value);
+    let text = text.trim_end_matches('0');
+    match text.strip_suffix('.') {
+        // avoid writing negative zero as "-0"
+        Some("-0") => "0".into(),
+        Some(int) => int.into(),
+        None => text.into(),
+    }
+}
+
+pub(crate) fn escape_text(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"))
+}
+
+fn escape_attribute(text: &str) -> Cow<'_, str> {
+    match escape_text(text) {
+        Cow::Borrowed(text) if !text.contains('"') => Cow::Borrowed(text),
+        text => Cow::Owned(text.replace('"', "&quot;")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(format_number(600.0f32), "600");
+        assert_eq!(format_number(-0.0f32), "0");
+        assert_eq!(format_number(0.1f32), "0.1");
+        assert_eq!(format_number(-12.5f64), "-12.5");
+        assert_eq!(format_number(0.000001f64), "0.000001");
+        assert_eq!(format_number(0.1f64 + 0.2), "0.3");
+        assert_eq!(format_number(1.0f64 / 3.0), "0.3333333333");
+        assert_eq!(format_number(2.0f32 / 3.0), "0.6666667");
+        assert_eq!(format_number(-0.00000000001f64), "0");
+        assert_eq!(format_number(1e-11f64), "0");
+        assert_eq!(format_number(123456789.5f64), "123456789.5");
+        assert_eq!(format_number(1e20f64), "100000000000000000000");
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape_text("a < b & 'c'"), "a &lt; b &amp; 'c'");
+        assert_eq!(escape_attribute(r#"say "hi""#), "say &quot;hi&quot;");
+    }
+
+    #[test]
+    fn plist() {
+        let mut dict = plist::Value::Dictionary(Default::default());
+        let inner = dict.as_dictionary_mut().unwrap();
+        inner.insert("b".into(), Value::Real(750.0));
+        inner.insert("a".into(), Value::Array(vec![Value::Real(0.5), Value::Boolean(true)]));
+        inner.insert("c".into(), Value::Dictionary(Default::default()));
+        let text = String::from_utf8(plist_to_vec(&dict).unwrap()).unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+	<dict>
+		<key>a</key>
+		<array>
+			<real>0.5</real>
+			<true/>
+		</array>
+		<key>b</key>
+		<integer>750</integer>
+		<key>c</key>
+		<dict/>
+	</dict>
+</plist>
+"#;
+        assert_eq!(text, expected);
+    }
+}